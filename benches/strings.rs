@@ -0,0 +1,36 @@
+//! Times tokenizing a key-heavy document whose strings take the no-escape
+//! fast path against the same document with every string escaped.
+//!
+//! Run with `cargo bench --bench strings`.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use jason::lexer::tokenize;
+
+const RECORDS: usize = 10_000;
+const ITERATIONS: u32 = 20;
+
+fn document(key: &str) -> String {
+    let record = format!(
+        "{{\"{key}_id\": 1, \"{key}_name\": \"{key}\", \"{key}_enabled\": true}}",
+        key = key
+    );
+    format!("[{}]", vec![record; RECORDS].join(","))
+}
+
+fn time(input: &str) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        black_box(tokenize(black_box(input)).count());
+    }
+    start.elapsed() / ITERATIONS
+}
+
+fn main() {
+    // `\u0065` is `e`, so both documents have the same strings once cooked.
+    let plain = document("identifier");
+    let escaped = document("id\\u0065ntifi\\u0065r");
+    println!("no escapes:   {:?}/iter", time(&plain));
+    println!("with escapes: {:?}/iter", time(&escaped));
+}
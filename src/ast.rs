@@ -1,4 +1,16 @@
-#[derive(Debug, PartialEq, PartialOrd)]
+use alloc::{borrow::ToOwned, format, string::String, vec, vec::Vec};
+use core::{cmp::Ordering, mem};
+
+/// A parsed JSON value.
+///
+/// The derived `PartialOrd` first orders nodes by variant, in declaration
+/// order, so `Object < Array < Str < Int < Float < True < False < Null`, and
+/// only then by contents. This is a structural order, not a numeric or
+/// JSON-aware one: `Int(2) < Float(1.0)` and `True < False`. Objects compare
+/// their members pairwise in document order and arrays their elements, like
+/// slices. A `Float(NaN)` is unordered with respect to any other `Float`.
+/// Changing the order of the variants changes this ordering.
+#[derive(Debug, Clone, PartialEq, PartialOrd)]
 pub enum Node {
     Object(Vec<(String, Node)>),
     Array(Vec<Node>),
@@ -9,3 +21,1515 @@ pub enum Node {
     False,
     Null,
 }
+
+/// Error returned by the `checked_*` accessors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccessError {
+    /// The node isn't an object (for keys) or an array (for indices).
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// The object has no member with this key.
+    MissingKey(String),
+    /// The array has only `len` elements.
+    IndexOutOfRange { index: usize, len: usize },
+}
+
+/// Error returned when a node isn't of the type an operation requires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeError {
+    pub expected: &'static str,
+    pub found: &'static str,
+}
+
+/// Error returned by `Node::try_as_i64`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NumberError {
+    /// The node isn't a number.
+    NotANumber { found: &'static str },
+    /// The `Float` has a fractional part.
+    Fractional,
+    /// The `Float` doesn't fit in an `isize`, including `NaN` and infinities.
+    OutOfRange,
+}
+
+/// What the elements of an array have in common, see
+/// `Node::array_element_type`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayElementType {
+    /// The array has no elements.
+    Empty,
+    /// Every element has this `Node::type_name`.
+    Uniform(&'static str),
+    /// The elements have different types.
+    Mixed,
+}
+
+/// Error returned by `Node::try_object`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildError {
+    /// More than one member has this key.
+    DuplicateKey(String),
+}
+
+/// Which member `Node::dedup_keys` keeps among members with the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupStrategy {
+    /// Keep the value of the first member, like `Node::get` does.
+    FirstWins,
+    /// Keep the value of the last member, like `JSON.parse` does.
+    LastWins,
+}
+
+/// How `Node::normalize_numbers` should rewrite numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberNormalize {
+    /// Convert every `Int` to a `Float`.
+    ///
+    /// Integers beyond ±2^53 can't be represented exactly by an `f64` and are
+    /// rounded to the nearest representable value.
+    AllFloat,
+    /// Convert every `Float` without a fractional part that fits in an `isize`
+    /// to an `Int`.
+    IntWhenIntegral,
+}
+
+impl Node {
+    /// An empty object with room for at least `n` members.
+    pub fn object_with_capacity(n: usize) -> Node {
+        Node::Object(Vec::with_capacity(n))
+    }
+
+    /// An empty array with room for at least `n` elements.
+    pub fn array_with_capacity(n: usize) -> Node {
+        Node::Array(Vec::with_capacity(n))
+    }
+
+    /// Builds an object from `members`, failing if two of them share a key.
+    pub fn try_object<I: IntoIterator<Item = (String, Node)>>(
+        members: I,
+    ) -> Result<Node, BuildError> {
+        let mut object: Vec<(String, Node)> = vec![];
+        for (key, value) in members {
+            if object.iter().any(|(k, _)| *k == key) {
+                return Err(BuildError::DuplicateKey(key));
+            }
+            object.push((key, value));
+        }
+        Ok(Node::Object(object))
+    }
+
+    /// The JSON type of this node: `"object"`, `"array"`, `"string"`,
+    /// `"number"`, `"boolean"` or `"null"`.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Node::Object(_) => "object",
+            Node::Array(_) => "array",
+            Node::Str(_) => "string",
+            Node::Int(_) | Node::Float(_) => "number",
+            Node::True | Node::False => "boolean",
+            Node::Null => "null",
+        }
+    }
+
+    /// JavaScript-like truthiness.
+    ///
+    /// `Null`, `False`, `0`, `0.0` (either sign), `NaN` and the empty string
+    /// are falsy. Everything else is truthy, including empty arrays and
+    /// objects.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Node::Null | Node::False => false,
+            Node::Int(i) => *i != 0,
+            Node::Float(f) => *f != 0.0 && !f.is_nan(),
+            Node::Str(s) => !s.is_empty(),
+            Node::True | Node::Object(_) | Node::Array(_) => true,
+        }
+    }
+
+    /// Moves this node out, leaving `Null` in its place.
+    pub fn take(&mut self) -> Node {
+        mem::replace(self, Node::Null)
+    }
+
+    /// Appends `value` to this array.
+    pub fn push(&mut self, value: Node) -> Result<(), TypeError> {
+        self.extend([value])
+    }
+
+    /// Appends every node yielded by `iter` to this array.
+    pub fn extend<I: IntoIterator<Item = Node>>(&mut self, iter: I) -> Result<(), TypeError> {
+        match self {
+            Node::Array(elements) => {
+                elements.extend(iter);
+                Ok(())
+            }
+            _ => Err(TypeError {
+                expected: "array",
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// The keys of this object in document order, or `None` if this isn't an
+    /// object. A duplicated key appears once per member.
+    pub fn keys(&self) -> Option<Vec<&str>> {
+        match self {
+            Node::Object(members) => Some(members.iter().map(|(key, _)| key.as_str()).collect()),
+            _ => None,
+        }
+    }
+
+    /// The members of this object in document order, with mutable values, or
+    /// `None` if this isn't an object.
+    pub fn entries_mut(&mut self) -> Option<impl Iterator<Item = (&str, &mut Node)>> {
+        match self {
+            Node::Object(members) => Some(members.iter_mut().map(|(k, v)| (k.as_str(), v))),
+            _ => None,
+        }
+    }
+
+    /// The elements of this array, mutably, or `None` if this isn't an array.
+    pub fn elements_mut(&mut self) -> Option<impl Iterator<Item = &mut Node>> {
+        match self {
+            Node::Array(elements) => Some(elements.iter_mut()),
+            _ => None,
+        }
+    }
+
+    /// The number of elements of this array, or `None` if this isn't an
+    /// array.
+    pub fn array_len(&self) -> Option<usize> {
+        match self {
+            Node::Array(elements) => Some(elements.len()),
+            _ => None,
+        }
+    }
+
+    /// Whether the elements of this array all have the same `type_name`, or
+    /// `None` if this isn't an array.
+    pub fn array_element_type(&self) -> Option<ArrayElementType> {
+        let elements = match self {
+            Node::Array(elements) => elements,
+            _ => return None,
+        };
+        let ty = match elements.first() {
+            Some(first) => first.type_name(),
+            None => return Some(ArrayElementType::Empty),
+        };
+        if elements.iter().all(|element| element.type_name() == ty) {
+            Some(ArrayElementType::Uniform(ty))
+        } else {
+            Some(ArrayElementType::Mixed)
+        }
+    }
+
+    /// Estimates the bytes of memory used by this tree, the node itself
+    /// included.
+    ///
+    /// This adds up the capacities of every string and vector in the tree,
+    /// it doesn't account for the allocator's own overhead.
+    pub fn memory_size(&self) -> usize {
+        mem::size_of::<Node>() + self.heap_size()
+    }
+
+    /// Bytes allocated on the heap by this node and its descendants.
+    pub(crate) fn heap_size(&self) -> usize {
+        match self {
+            Node::Object(members) => {
+                let children: usize = members
+                    .iter()
+                    .map(|(key, value)| key.capacity() + value.heap_size())
+                    .sum();
+                members.capacity() * mem::size_of::<(String, Node)>() + children
+            }
+            Node::Array(elements) => {
+                let children: usize = elements.iter().map(Node::heap_size).sum();
+                elements.capacity() * mem::size_of::<Node>() + children
+            }
+            Node::Str(s) => s.capacity(),
+            _ => 0,
+        }
+    }
+
+    /// Number of Unicode scalar values in this string, or `None` if this
+    /// isn't a string.
+    pub fn str_char_count(&self) -> Option<usize> {
+        match self {
+            Node::Str(s) => Some(s.chars().count()),
+            _ => None,
+        }
+    }
+
+    /// Length in bytes of this string once UTF-8 encoded, or `None` if this
+    /// isn't a string.
+    pub fn str_byte_len(&self) -> Option<usize> {
+        match self {
+            Node::Str(s) => Some(s.len()),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `Int`, or `None` for anything else, including
+    /// integral `Float`s like `5.0`.
+    pub fn as_i64_exact(&self) -> Option<isize> {
+        match self {
+            Node::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of a `Float`, or of an `Int` that an `f64`
+    /// represents exactly, i.e. within ±2^53. `None` for anything else.
+    pub fn as_f64_exact(&self) -> Option<f64> {
+        // Compared as an `i64` since `isize` may be too narrow to hold 2^53.
+        const MAX_EXACT: u64 = 1 << 53;
+        match self {
+            Node::Float(f) => Some(*f),
+            Node::Int(i) if (*i as i64).unsigned_abs() <= MAX_EXACT => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value of an `Int`, or of a `Float` without a fractional
+    /// part that fits in an `isize`.
+    pub fn try_as_i64(&self) -> Result<isize, NumberError> {
+        match self {
+            Node::Int(i) => Ok(*i),
+            Node::Float(f) => integral_f64_to_isize(*f).ok_or({
+                // Floats this large have no fractional part left.
+                if f.abs() < isize::MAX as f64 {
+                    NumberError::Fractional
+                } else {
+                    NumberError::OutOfRange
+                }
+            }),
+            _ => Err(NumberError::NotANumber {
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// Compares like `==`, except that a `Float` is equal to another number
+    /// if they're within `epsilon` of each other, an `Int` being widened to
+    /// an `f64` for this.
+    ///
+    /// Objects are compared member by member in order, like `==` does.
+    pub fn approx_eq(&self, other: &Node, epsilon: f64) -> bool {
+        match (self, other) {
+            (Node::Float(a), Node::Float(b)) => (a - b).abs() <= epsilon,
+            (Node::Float(f), Node::Int(i)) | (Node::Int(i), Node::Float(f)) => {
+                (f - *i as f64).abs() <= epsilon
+            }
+            (Node::Object(a), Node::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b)
+                        .all(|((ka, va), (kb, vb))| ka == kb && va.approx_eq(vb, epsilon))
+            }
+            (Node::Array(a), Node::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Whether `other` contains at least everything in this node, e.g. to
+    /// check that a response has some expected members.
+    ///
+    /// An object is a subset if `other` is an object with a member for each
+    /// of its keys, the first one if duplicated, whose value it is a subset
+    /// of. An array is a subset if `other` is an array of the same length
+    /// whose elements it is a subset of, position by position. Any other
+    /// node must be equal to `other`.
+    pub fn is_subset_of(&self, other: &Node) -> bool {
+        match (self, other) {
+            (Node::Object(members), Node::Object(_)) => members
+                .iter()
+                .all(|(key, value)| other.get(key).is_some_and(|o| value.is_subset_of(o))),
+            (Node::Array(a), Node::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.is_subset_of(b))
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Whether `needle` is equal to this node or any node nested in it.
+    /// Object keys aren't searched, only values.
+    ///
+    /// Floats are compared with `f64::total_cmp`, so a `Float(NaN)` needle
+    /// finds an identical `NaN` and `-0.0` doesn't find `0.0`.
+    pub fn deep_contains(&self, needle: &Node) -> bool {
+        if total_cmp(self, needle).is_eq() {
+            return true;
+        }
+        match self {
+            Node::Object(members) => members.iter().any(|(_, v)| v.deep_contains(needle)),
+            Node::Array(elements) => elements.iter().any(|e| e.deep_contains(needle)),
+            _ => false,
+        }
+    }
+
+    /// Whether this node or any node nested in it is the string `s`.
+    pub fn deep_contains_str(&self, s: &str) -> bool {
+        match self {
+            Node::Str(value) => value == s,
+            Node::Object(members) => members.iter().any(|(_, v)| v.deep_contains_str(s)),
+            Node::Array(elements) => elements.iter().any(|e| e.deep_contains_str(s)),
+            _ => false,
+        }
+    }
+
+    /// Returns the value of the first member with the given key, if this is
+    /// an object.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Like `get`, but matches keys case-insensitively, e.g. for HTTP header
+    /// names.
+    ///
+    /// Only ASCII letters are case folded, so `"É"` doesn't match `"é"`.
+    pub fn get_ci(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Object(members) => members
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Like `get`, but reports why the lookup failed.
+    pub fn checked_get(&self, key: &str) -> Result<&Node, AccessError> {
+        match self {
+            Node::Object(_) => self
+                .get(key)
+                .ok_or_else(|| AccessError::MissingKey(key.to_owned())),
+            _ => Err(AccessError::TypeMismatch {
+                expected: "object",
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// Returns the element at index `i`, reporting why the lookup failed.
+    pub fn checked_index(&self, i: usize) -> Result<&Node, AccessError> {
+        match self {
+            Node::Array(elements) => elements.get(i).ok_or(AccessError::IndexOutOfRange {
+                index: i,
+                len: elements.len(),
+            }),
+            _ => Err(AccessError::TypeMismatch {
+                expected: "array",
+                found: self.type_name(),
+            }),
+        }
+    }
+
+    /// Looks up several keys at once, in a single pass over the members.
+    ///
+    /// The result is parallel to `keys`. Like `get`, the first member with a
+    /// given key wins. Non-objects yield `None` for every key.
+    pub fn get_many<'a>(&'a self, keys: &[&str]) -> Vec<Option<&'a Node>> {
+        let mut found = vec![None; keys.len()];
+        if let Node::Object(members) = self {
+            for (key, value) in members {
+                for (slot, wanted) in found.iter_mut().zip(keys) {
+                    if slot.is_none() && key == wanted {
+                        *slot = Some(value);
+                    }
+                }
+            }
+        }
+        found
+    }
+
+    /// Returns the members of this object sorted by key, without reordering
+    /// the object itself. Non-objects have no entries.
+    ///
+    /// The sort is stable, so members with duplicate keys keep their original
+    /// relative order.
+    pub fn entries_sorted(&self) -> Vec<(&str, &Node)> {
+        let mut entries: Vec<(&str, &Node)> = match self {
+            Node::Object(members) => members.iter().map(|(k, v)| (k.as_str(), v)).collect(),
+            _ => vec![],
+        };
+        entries.sort_by_key(|(key, _)| *key);
+        entries
+    }
+
+    /// Calls `f` on every node in the tree, parents before their children.
+    pub fn walk<'a, F: FnMut(&'a Node)>(&'a self, f: &mut F) {
+        f(self);
+        match self {
+            Node::Object(members) => members.iter().for_each(|(_, value)| value.walk(f)),
+            Node::Array(elements) => elements.iter().for_each(|element| element.walk(f)),
+            _ => (),
+        }
+    }
+
+    /// Calls `f` on every node in the tree, parents before their children.
+    ///
+    /// Children are visited after `f` has been applied to their parent, so
+    /// nodes inserted by `f` are visited too.
+    pub fn walk_mut<F: FnMut(&mut Node)>(&mut self, f: &mut F) {
+        f(self);
+        match self {
+            Node::Object(members) => members.iter_mut().for_each(|(_, value)| value.walk_mut(f)),
+            Node::Array(elements) => elements.iter_mut().for_each(|element| element.walk_mut(f)),
+            _ => (),
+        }
+    }
+
+    /// Replaces every string value in the tree with `f` applied to it.
+    ///
+    /// Object keys are left untouched.
+    pub fn map_strings<F: FnMut(&str) -> String>(&mut self, mut f: F) {
+        self.walk_mut(&mut |node| {
+            if let Node::Str(s) = node {
+                *s = f(s);
+            }
+        });
+    }
+
+    /// Replaces every number in the tree with `f` applied to it.
+    ///
+    /// `Int`s are widened to `f64` before calling `f`. The result stays an
+    /// `Int` if it is integral and fits in an `isize`, otherwise it becomes a
+    /// `Float`. `Float`s always stay `Float`s.
+    pub fn map_numbers<F: FnMut(f64) -> f64>(&mut self, mut f: F) {
+        self.walk_mut(&mut |node| match node {
+            Node::Int(i) => {
+                let mapped = f(*i as f64);
+                *node = match integral_f64_to_isize(mapped) {
+                    Some(i) => Node::Int(i),
+                    None => Node::Float(mapped),
+                };
+            }
+            Node::Float(x) => *x = f(*x),
+            _ => (),
+        });
+    }
+
+    /// Collects the values of every object member named `key`, at any depth,
+    /// in document order.
+    pub fn select_all_by_key(&self, key: &str) -> Vec<&Node> {
+        let mut found = vec![];
+        self.select_by_key_into(key, &mut found);
+        found
+    }
+
+    fn select_by_key_into<'a>(&'a self, key: &str, found: &mut Vec<&'a Node>) {
+        match self {
+            Node::Object(members) => {
+                for (k, v) in members {
+                    if k == key {
+                        found.push(v);
+                    }
+                    v.select_by_key_into(key, found);
+                }
+            }
+            Node::Array(elements) => elements
+                .iter()
+                .for_each(|e| e.select_by_key_into(key, found)),
+            _ => (),
+        }
+    }
+
+    /// Returns the value of the first object member named `key`, at any
+    /// depth, in document order.
+    pub fn select_first_by_key(&self, key: &str) -> Option<&Node> {
+        match self {
+            Node::Object(members) => members.iter().find_map(|(k, v)| {
+                if k == key {
+                    Some(v)
+                } else {
+                    v.select_first_by_key(key)
+                }
+            }),
+            Node::Array(elements) => elements.iter().find_map(|e| e.select_first_by_key(key)),
+            _ => None,
+        }
+    }
+
+    /// Rewrites every number in the tree according to `mode`.
+    pub fn normalize_numbers(&mut self, mode: NumberNormalize) {
+        self.walk_mut(&mut |node| match (mode, &*node) {
+            (NumberNormalize::AllFloat, Node::Int(i)) => *node = Node::Float(*i as f64),
+            (NumberNormalize::IntWhenIntegral, Node::Float(f)) => {
+                if let Some(i) = integral_f64_to_isize(*f) {
+                    *node = Node::Int(i);
+                }
+            }
+            _ => (),
+        });
+    }
+
+    /// Replaces every non-finite `Float` (`NaN` or infinite) in the tree with
+    /// a clone of `replacement`, e.g. `Node::Null`.
+    pub fn sanitize_floats(&mut self, replacement: Node) {
+        self.walk_mut(&mut |node| {
+            if let Node::Float(f) = node {
+                if !f.is_finite() {
+                    *node = replacement.clone();
+                }
+            }
+        });
+    }
+
+    /// Counts the nodes in the tree, including this one, matching `pred`.
+    pub fn count_where<F: Fn(&Node) -> bool>(&self, pred: F) -> usize {
+        let mut count = 0;
+        self.walk(&mut |node| {
+            if pred(node) {
+                count += 1;
+            }
+        });
+        count
+    }
+
+    /// Sums every number in the tree, widening `Int`s to `f64`.
+    pub fn sum_numbers(&self) -> f64 {
+        let mut sum = 0.0;
+        self.walk(&mut |node| match node {
+            Node::Int(i) => sum += *i as f64,
+            Node::Float(f) => sum += f,
+            _ => (),
+        });
+        sum
+    }
+
+    /// Collects every string value in the tree, in document order.
+    ///
+    /// Object keys are not included.
+    pub fn collect_strings(&self) -> Vec<&str> {
+        let mut strings = vec![];
+        self.walk(&mut |node| {
+            if let Node::Str(s) = node {
+                strings.push(s.as_str());
+            }
+        });
+        strings
+    }
+
+    /// Truncates every string value longer than `max_len` chars to its first
+    /// `max_len` chars followed by `ellipsis`.
+    ///
+    /// Lengths are counted in Unicode scalar values, so multibyte chars are
+    /// never split.
+    pub fn truncate_strings(&mut self, max_len: usize, ellipsis: &str) {
+        self.walk_mut(&mut |node| {
+            if let Node::Str(s) = node {
+                if let Some((end, _)) = s.char_indices().nth(max_len) {
+                    s.truncate(end);
+                    s.push_str(ellipsis);
+                }
+            }
+        });
+    }
+
+    /// Trims every string value and collapses each run of whitespace inside
+    /// it to a single space, e.g. `"  a \t\n b "` becomes `"a b"`.
+    ///
+    /// Whitespace is as defined by Unicode, so non-breaking spaces collapse
+    /// too. This changes the data, so only use it when insignificant
+    /// whitespace is known to be noise. Object keys are left untouched.
+    pub fn normalize_string_whitespace(&mut self) {
+        self.map_strings(|s| {
+            let mut normalized = String::with_capacity(s.len());
+            for word in s.split_whitespace() {
+                if !normalized.is_empty() {
+                    normalized.push(' ');
+                }
+                normalized.push_str(word);
+            }
+            normalized
+        });
+    }
+
+    /// Removes elements of this array equal to an earlier one, keeping the
+    /// first occurrence of each in order. No-op if this isn't an array.
+    ///
+    /// Floats are compared with `f64::total_cmp` like in
+    /// `dedup_array_sorted`, so identical `NaN`s are deduplicated too and
+    /// `-0.0` is kept apart from `0.0`. This takes quadratic time, see
+    /// `dedup_array_sorted` for large arrays.
+    pub fn dedup_array(&mut self) {
+        if let Node::Array(elements) = self {
+            let mut kept: Vec<Node> = Vec::with_capacity(elements.len());
+            for element in mem::take(elements) {
+                if !kept.iter().any(|k| total_cmp(k, &element).is_eq()) {
+                    kept.push(element);
+                }
+            }
+            *elements = kept;
+        }
+    }
+
+    /// Sorts this array and removes duplicate elements, in O(n log n). No-op
+    /// if this isn't an array.
+    ///
+    /// Elements are ordered like `PartialOrd`, except that floats compare
+    /// with `f64::total_cmp`, so identical `NaN`s are deduplicated too and
+    /// `-0.0` is kept apart from `0.0`.
+    pub fn dedup_array_sorted(&mut self) {
+        if let Node::Array(elements) = self {
+            elements.sort_by(total_cmp);
+            elements.dedup_by(|a, b| total_cmp(a, b).is_eq());
+        }
+    }
+
+    /// Truncates every array longer than `max_len` to its first `max_len`
+    /// elements, e.g. to preview a large document.
+    ///
+    /// With `note_omitted`, a `"…N more"` string is appended to each truncated
+    /// array in place of the `N` dropped elements.
+    pub fn truncate_arrays(&mut self, max_len: usize, note_omitted: bool) {
+        self.walk_mut(&mut |node| {
+            if let Node::Array(elements) = node {
+                let omitted = elements.len().saturating_sub(max_len);
+                if omitted > 0 {
+                    elements.truncate(max_len);
+                    if note_omitted {
+                        elements.push(Node::Str(format!("…{} more", omitted)));
+                    }
+                }
+            }
+        });
+    }
+
+    /// Whether any object in the tree has two members with the same key.
+    ///
+    /// Duplicates are kept in document order by `parse` and written back out
+    /// as is, so this lets tools warn about them, or `dedup_keys` remove them.
+    pub fn has_duplicate_keys(&self) -> bool {
+        match self {
+            Node::Object(members) => {
+                let duplicated = members
+                    .iter()
+                    .enumerate()
+                    .any(|(i, (key, _))| members[..i].iter().any(|(k, _)| k == key));
+                duplicated || members.iter().any(|(_, value)| value.has_duplicate_keys())
+            }
+            Node::Array(elements) => elements.iter().any(Node::has_duplicate_keys),
+            _ => false,
+        }
+    }
+
+    /// Collapses members with duplicate keys into one, in every object of the
+    /// tree, keeping the value chosen by `strategy`.
+    ///
+    /// The remaining member takes the place of the first duplicate, as in a
+    /// JavaScript object. Values that are dropped are not visited.
+    pub fn dedup_keys(&mut self, strategy: DedupStrategy) {
+        self.walk_mut(&mut |node| {
+            if let Node::Object(members) = node {
+                let mut deduped: Vec<(String, Node)> = Vec::with_capacity(members.len());
+                for (key, value) in mem::take(members) {
+                    match deduped.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, kept)) if strategy == DedupStrategy::LastWins => *kept = value,
+                        Some(_) => (),
+                        None => deduped.push((key, value)),
+                    }
+                }
+                *members = deduped;
+            }
+        });
+    }
+
+    /// Recursively removes object members whose value is `Null`, and `Null`
+    /// array elements too if `in_arrays` is set.
+    ///
+    /// Each container is pruned before recursing into the values it keeps,
+    /// so a container left empty by pruning is kept rather than removed.
+    pub fn prune_nulls(&mut self, in_arrays: bool) {
+        match self {
+            Node::Object(members) => {
+                members.retain(|(_, value)| *value != Node::Null);
+                for (_, value) in members {
+                    value.prune_nulls(in_arrays);
+                }
+            }
+            Node::Array(elements) => {
+                if in_arrays {
+                    elements.retain(|element| *element != Node::Null);
+                }
+                for element in elements {
+                    element.prune_nulls(in_arrays);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    /// Fills in members of `defaults` missing from this object, recursing into
+    /// objects present in both, without overwriting any existing value.
+    ///
+    /// Arrays and scalars are left as is, as is this node if either isn't an
+    /// object.
+    pub fn apply_defaults(&mut self, defaults: &Node) {
+        if let (Node::Object(members), Node::Object(defaults)) = (self, defaults) {
+            for (key, default) in defaults {
+                match members.iter_mut().find(|(k, _)| k == key) {
+                    Some((_, value)) => value.apply_defaults(default),
+                    None => members.push((key.clone(), default.clone())),
+                }
+            }
+        }
+    }
+
+    /// Deep merges `other` into this node, matching array elements by the
+    /// value of their `key` member.
+    ///
+    /// Objects are merged member by member, members only in `other` being
+    /// appended. Arrays are merged element by element: an object element of
+    /// `other` is merged into the first element of this array whose `key`
+    /// member is equal, if any. Elements of `other` that aren't matched,
+    /// including those lacking `key` or that aren't objects, are appended.
+    /// Any other value is replaced by a clone of `other`.
+    pub fn merge_arrays_by_key(&mut self, other: &Node, key: &str) {
+        match (self, other) {
+            (Node::Object(members), Node::Object(others)) => {
+                for (k, v) in others {
+                    match members.iter_mut().find(|(m, _)| m == k) {
+                        Some((_, member)) => member.merge_arrays_by_key(v, key),
+                        None => members.push((k.clone(), v.clone())),
+                    }
+                }
+            }
+            (Node::Array(elements), Node::Array(others)) => {
+                for other in others {
+                    let matched = other
+                        .get(key)
+                        .and_then(|id| elements.iter_mut().find(|e| e.get(key) == Some(id)));
+                    match matched {
+                        Some(element) => element.merge_arrays_by_key(other, key),
+                        None => elements.push(other.clone()),
+                    }
+                }
+            }
+            (this, other) => *this = other.clone(),
+        }
+    }
+
+    /// Deep merges `other` into this node, `other` winning on conflicts.
+    ///
+    /// Objects are merged member by member, members only in `other` being
+    /// appended. Any other value, arrays included, is replaced by `other`.
+    pub fn deep_merge(&mut self, other: Node) {
+        match (self, other) {
+            (Node::Object(members), Node::Object(others)) => {
+                for (k, v) in others {
+                    match members.iter_mut().find(|(m, _)| *m == k) {
+                        Some((_, member)) => member.deep_merge(v),
+                        None => members.push((k, v)),
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    /// Deep merges `layers` in order into the first one, so later layers win,
+    /// e.g. defaults, then environment, then overrides.
+    ///
+    /// Returns `Null` if there are no layers.
+    pub fn merge_all<I: IntoIterator<Item = Node>>(layers: I) -> Node {
+        let mut layers = layers.into_iter();
+        let mut merged = layers.next().unwrap_or(Node::Null);
+        for layer in layers {
+            merged.deep_merge(layer);
+        }
+        merged
+    }
+}
+
+/// The order of `PartialOrd` made total by comparing floats with
+/// `f64::total_cmp`.
+pub(crate) fn total_cmp(a: &Node, b: &Node) -> Ordering {
+    fn rank(node: &Node) -> u8 {
+        match node {
+            Node::Object(_) => 0,
+            Node::Array(_) => 1,
+            Node::Str(_) => 2,
+            Node::Int(_) => 3,
+            Node::Float(_) => 4,
+            Node::True => 5,
+            Node::False => 6,
+            Node::Null => 7,
+        }
+    }
+    match (a, b) {
+        (Node::Object(a), Node::Object(b)) => a
+            .iter()
+            .zip(b)
+            .map(|((ka, va), (kb, vb))| ka.cmp(kb).then_with(|| total_cmp(va, vb)))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Node::Array(a), Node::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| total_cmp(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| a.len().cmp(&b.len())),
+        (Node::Str(a), Node::Str(b)) => a.cmp(b),
+        (Node::Int(a), Node::Int(b)) => a.cmp(b),
+        (Node::Float(a), Node::Float(b)) => a.total_cmp(b),
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Converts `f` to an `isize` if it is integral and in range.
+pub(crate) fn integral_f64_to_isize(f: f64) -> Option<isize> {
+    // `isize::MAX as f64` rounds up, so it is itself out of range.
+    let in_range = f >= isize::MIN as f64 && f < isize::MAX as f64;
+    // NaN fails the range check and `as` truncates the fraction otherwise.
+    if in_range && f == (f as isize) as f64 {
+        Some(f as isize)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        AccessError, ArrayElementType, BuildError, DedupStrategy, Node, NumberError,
+        NumberNormalize, TypeError,
+    };
+    use crate::parser::parse;
+
+    const SAMPLE: &str = "[{ \"name\": \"Adrien\", \"age\": 23, \"hungry\": true, \"health\": 0.9, \"girlfriend\": null }, { \"name\": \"Bob\", \"age\": 40, \"girlfriend\": null }]";
+
+    #[test]
+    fn it_maps_strings() {
+        let mut node = parse("{\" key \": [\"  a \", {\"b\": \"c  \"}, 1]}").unwrap();
+        node.map_strings(|s| s.trim().to_owned());
+        let expected = parse("{\" key \": [\"a\", {\"b\": \"c\"}, 1]}").unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_maps_numbers() {
+        let mut node = parse("{\"a\": [1, 0.5, {\"b\": -3}], \"c\": \"4\"}").unwrap();
+        node.map_numbers(|x| x * 10.0);
+        let expected = parse("{\"a\": [10, 5.0, {\"b\": -30}], \"c\": \"4\"}").unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_maps_integers_to_floats_when_not_integral() {
+        let mut node = Node::Int(3);
+        node.map_numbers(|x| x / 2.0);
+        assert_eq!(node, Node::Float(1.5));
+    }
+
+    #[test]
+    fn it_counts_matching_nodes() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.count_where(|n| *n == Node::Null), 2);
+        assert_eq!(node.count_where(|n| matches!(n, Node::Object(_))), 2);
+        assert_eq!(node.count_where(|n| matches!(n, Node::Array(_))), 1);
+        assert_eq!(node.count_where(|_| true), 11);
+    }
+
+    #[test]
+    fn it_sums_numbers() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.sum_numbers(), 63.9);
+        assert_eq!(Node::Str("1".into()).sum_numbers(), 0.0);
+    }
+
+    #[test]
+    fn it_collects_strings() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.collect_strings(), vec!["Adrien", "Bob"]);
+    }
+
+    #[test]
+    fn it_truncates_long_strings() {
+        let mut node = parse("[\"abcdefgh\", \"abc\", {\"k\": \"héllo wörld\"}]").unwrap();
+        node.truncate_strings(3, "...");
+        let expected = parse("[\"abc...\", \"abc\", {\"k\": \"hél...\"}]").unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_normalizes_string_whitespace() {
+        let mut node =
+            parse("{\" k  \": [\"  a   b  \", \"c\\t\\n\u{a0}d\", \"   \", 1, null]}").unwrap();
+        node.normalize_string_whitespace();
+        let expected = parse("{\" k  \": [\"a b\", \"c d\", \"\", 1, null]}").unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_dedups_array_elements() {
+        let mut node = parse("[1, 2, 1, 3, 2]").unwrap();
+        node.dedup_array();
+        assert_eq!(node, parse("[1, 2, 3]").unwrap());
+
+        let mut node = parse("[{\"a\": 1}, \"x\", {\"a\": 1}, 1.0, 1]").unwrap();
+        node.dedup_array();
+        assert_eq!(node, parse("[{\"a\": 1}, \"x\", 1.0, 1]").unwrap());
+
+        let mut node = Node::Array(vec![
+            Node::Float(f64::NAN),
+            Node::Float(0.0),
+            Node::Float(f64::NAN),
+            Node::Float(-0.0),
+        ]);
+        node.dedup_array();
+        match node {
+            Node::Array(elements) => match elements[..] {
+                [Node::Float(nan), Node::Float(zero), Node::Float(negative_zero)] => {
+                    assert!(nan.is_nan());
+                    assert!(zero.is_sign_positive());
+                    assert!(negative_zero.is_sign_negative());
+                }
+                _ => panic!("unexpected elements {:?}", elements),
+            },
+            _ => unreachable!(),
+        }
+
+        let mut node = parse("{\"a\": [1, 1]}").unwrap();
+        node.dedup_array();
+        assert_eq!(node, parse("{\"a\": [1, 1]}").unwrap());
+    }
+
+    #[test]
+    fn it_dedups_sorted_array_elements() {
+        let mut node = parse("[3, 1, 2, 1, 3, 2]").unwrap();
+        node.dedup_array_sorted();
+        assert_eq!(node, parse("[1, 2, 3]").unwrap());
+
+        let mut node = Node::Array(vec![
+            Node::Float(f64::NAN),
+            Node::Null,
+            Node::Str("b".into()),
+            Node::Float(f64::NAN),
+            parse("[1, 2]").unwrap(),
+            Node::Str("a".into()),
+            parse("[1]").unwrap(),
+            Node::Null,
+        ]);
+        node.dedup_array_sorted();
+        let elements = match node {
+            Node::Array(elements) => elements,
+            _ => unreachable!(),
+        };
+        assert_eq!(elements.len(), 6);
+        assert_eq!(elements[0], parse("[1]").unwrap());
+        assert_eq!(elements[1], parse("[1, 2]").unwrap());
+        assert_eq!(elements[2], Node::Str("a".into()));
+        assert_eq!(elements[3], Node::Str("b".into()));
+        assert!(matches!(elements[4], Node::Float(f) if f.is_nan()));
+        assert_eq!(elements[5], Node::Null);
+    }
+
+    #[test]
+    fn it_truncates_long_arrays() {
+        let mut node = parse("{\"a\": [1, 2, 3, 4, 5], \"b\": [[1, 2, 3], 4]}").unwrap();
+        let mut noted = node.clone();
+        node.truncate_arrays(2, false);
+        assert_eq!(node, parse("{\"a\": [1, 2], \"b\": [[1, 2], 4]}").unwrap());
+
+        noted.truncate_arrays(2, true);
+        let expected = parse("{\"a\": [1, 2, \"…3 more\"], \"b\": [[1, 2, \"…1 more\"], 4]}");
+        assert_eq!(noted, expected.unwrap());
+    }
+
+    #[test]
+    fn it_gets_a_member() {
+        let node = parse("{\"a\": 1, \"a\": 2}").unwrap();
+        assert_eq!(node.get("a"), Some(&Node::Int(1)));
+        assert_eq!(node.get("b"), None);
+        assert_eq!(Node::Array(vec![]).get("a"), None);
+    }
+
+    #[test]
+    fn it_gets_many_members() {
+        let node = parse("{\"host\": \"localhost\", \"port\": 8080, \"debug\": true}").unwrap();
+        assert_eq!(
+            node.get_many(&["port", "user", "host"]),
+            vec![
+                Some(&Node::Int(8080)),
+                None,
+                Some(&Node::Str("localhost".into()))
+            ]
+        );
+        assert_eq!(Node::Null.get_many(&["a", "b"]), vec![None, None]);
+    }
+
+    #[test]
+    fn it_returns_sorted_entries() {
+        let node = parse("{\"b\": 1, \"a\": 2, \"c\": 3, \"a\": 4}").unwrap();
+        assert_eq!(
+            node.entries_sorted(),
+            vec![
+                ("a", &Node::Int(2)),
+                ("a", &Node::Int(4)),
+                ("b", &Node::Int(1)),
+                ("c", &Node::Int(3))
+            ]
+        );
+        assert_eq!(
+            node,
+            parse("{\"b\": 1, \"a\": 2, \"c\": 3, \"a\": 4}").unwrap()
+        );
+        assert_eq!(Node::True.entries_sorted(), vec![]);
+    }
+
+    #[test]
+    fn it_sanitizes_non_finite_floats() {
+        let mut node = Node::Array(vec![
+            Node::Float(f64::NAN),
+            Node::Object(vec![("a".into(), Node::Float(f64::INFINITY))]),
+            Node::Float(f64::NEG_INFINITY),
+            Node::Float(1.5),
+        ]);
+        node.sanitize_floats(Node::Null);
+        let expected = parse("[null, {\"a\": null}, null, 1.5]").unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_reports_checked_access_errors() {
+        let node = parse("{\"a\": [1, 2]}").unwrap();
+        assert_eq!(
+            node.checked_get("a").and_then(|a| a.checked_index(1)),
+            Ok(&Node::Int(2))
+        );
+        assert_eq!(
+            node.checked_get("b"),
+            Err(AccessError::MissingKey("b".into()))
+        );
+        assert_eq!(
+            node.checked_index(0),
+            Err(AccessError::TypeMismatch {
+                expected: "array",
+                found: "object"
+            })
+        );
+        let array = node.checked_get("a").unwrap();
+        assert_eq!(
+            array.checked_get("a"),
+            Err(AccessError::TypeMismatch {
+                expected: "object",
+                found: "array"
+            })
+        );
+        assert_eq!(
+            array.checked_index(2),
+            Err(AccessError::IndexOutOfRange { index: 2, len: 2 })
+        );
+    }
+
+    #[test]
+    fn it_checks_truthiness() {
+        for falsy in ["null", "false", "0", "0.0", "-0.0", "\"\""] {
+            assert!(
+                !parse(falsy).unwrap().is_truthy(),
+                "{} should be falsy",
+                falsy
+            );
+        }
+        assert!(!Node::Float(f64::NAN).is_truthy());
+        for truthy in ["true", "1", "-0.5", "\"0\"", "\"false\"", "[]", "{}"] {
+            assert!(
+                parse(truthy).unwrap().is_truthy(),
+                "{} should be truthy",
+                truthy
+            );
+        }
+    }
+
+    #[test]
+    fn it_selects_values_by_key_at_any_depth() {
+        let node = parse("{\"children\": [{\"type\": \"item\", \"value\": {\"type\": \"number\"}}, {\"type\": \"list\", \"children\": []}], \"type\": \"list\"}").unwrap();
+        let types: Vec<_> = node
+            .select_all_by_key("type")
+            .into_iter()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(types, ["\"item\"", "\"number\"", "\"list\"", "\"list\""]);
+        assert_eq!(
+            node.select_first_by_key("value").map(|n| n.type_name()),
+            Some("object")
+        );
+        assert_eq!(
+            node.select_first_by_key("type"),
+            Some(&Node::Str("item".into()))
+        );
+        assert_eq!(node.select_first_by_key("id"), None);
+    }
+
+    #[test]
+    fn it_normalizes_numbers_to_floats() {
+        let mut node = parse("[1, 2.5, {\"a\": -3, \"b\": 4.0}]").unwrap();
+        node.normalize_numbers(NumberNormalize::AllFloat);
+        assert_eq!(
+            node,
+            parse("[1.0, 2.5, {\"a\": -3.0, \"b\": 4.0}]").unwrap()
+        );
+    }
+
+    #[test]
+    fn it_normalizes_integral_numbers_to_ints() {
+        let mut node = parse("[1, 2.5, {\"a\": -3.0, \"b\": 4e0, \"c\": 1e300}]").unwrap();
+        node.normalize_numbers(NumberNormalize::IntWhenIntegral);
+        assert_eq!(
+            node,
+            parse("[1, 2.5, {\"a\": -3, \"b\": 4, \"c\": 1e300}]").unwrap()
+        );
+    }
+
+    #[test]
+    fn it_pushes_and_extends_arrays() {
+        let mut node = Node::Array(vec![]);
+        node.push(Node::Int(1)).unwrap();
+        node.push(Node::Null).unwrap();
+        node.extend((2..4).map(Node::Int)).unwrap();
+        assert_eq!(node, parse("[1, null, 2, 3]").unwrap());
+    }
+
+    #[test]
+    fn it_fails_to_push_to_non_arrays() {
+        let mut node = parse("{}").unwrap();
+        let err = TypeError {
+            expected: "array",
+            found: "object",
+        };
+        assert_eq!(node.push(Node::Null), Err(err.clone()));
+        assert_eq!(node.extend(vec![Node::Null]), Err(err));
+        assert_eq!(node, Node::Object(vec![]));
+    }
+
+    #[test]
+    fn it_merges_arrays_by_key() {
+        let mut node = parse(
+            "{\"servers\": [{\"id\": \"a\", \"port\": 80, \"tls\": {\"on\": false}}, {\"id\": \"b\", \"port\": 81}]}",
+        )
+        .unwrap();
+        let overlay = parse(
+            "{\"servers\": [{\"id\": \"a\", \"tls\": {\"on\": true}}, {\"id\": \"c\"}, {\"port\": 82}], \"debug\": true}",
+        )
+        .unwrap();
+        node.merge_arrays_by_key(&overlay, "id");
+        let expected = parse(
+            "{\"servers\": [{\"id\": \"a\", \"port\": 80, \"tls\": {\"on\": true}}, {\"id\": \"b\", \"port\": 81}, {\"id\": \"c\"}, {\"port\": 82}], \"debug\": true}",
+        )
+        .unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_prunes_nulls() {
+        let input = "{\"a\": null, \"b\": {\"c\": null, \"d\": 1}, \"e\": [null, {\"f\": null}], \"g\": false}";
+
+        let mut node = parse(input).unwrap();
+        node.prune_nulls(false);
+        let expected = "{\"b\": {\"d\": 1}, \"e\": [null, {}], \"g\": false}";
+        assert_eq!(node, parse(expected).unwrap());
+
+        let mut node = parse(input).unwrap();
+        node.prune_nulls(true);
+        let expected = "{\"b\": {\"d\": 1}, \"e\": [{}], \"g\": false}";
+        assert_eq!(node, parse(expected).unwrap());
+    }
+
+    #[test]
+    fn it_orders_nodes_by_variant_then_contents() {
+        let ascending = parse("[{}, [], \"\", 0, 0.0, true, false, null]").unwrap();
+        let Node::Array(ascending) = ascending else {
+            unreachable!()
+        };
+        for pair in ascending.windows(2) {
+            assert!(pair[0] < pair[1], "{:?} < {:?}", pair[0], pair[1]);
+        }
+
+        assert!(Node::Int(2) < Node::Float(1.0));
+        assert!(Node::True < Node::False);
+        assert!(Node::Int(-1) < Node::Int(0));
+        assert!(Node::Str("a".into()) < Node::Str("b".into()));
+        assert!(parse("[1, 2]").unwrap() < parse("[1, 3]").unwrap());
+        assert!(parse("[1]").unwrap() < parse("[1, 0]").unwrap());
+        assert!(parse("{\"a\": 2}").unwrap() < parse("{\"b\": 1}").unwrap());
+        assert_eq!(Node::Float(f64::NAN).partial_cmp(&Node::Float(0.0)), None);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn it_rejects_ints_a_float_cant_represent() {
+        assert_eq!(Node::Int(1 << 53).as_f64_exact(), Some(9007199254740992.0));
+        assert_eq!(
+            Node::Int(-(1 << 53)).as_f64_exact(),
+            Some(-9007199254740992.0)
+        );
+        assert_eq!(Node::Int((1 << 53) + 1).as_f64_exact(), None);
+        assert_eq!(Node::Int(isize::MIN).as_f64_exact(), None);
+    }
+
+    #[test]
+    fn it_extracts_exact_numbers() {
+        assert_eq!(Node::Int(5).as_i64_exact(), Some(5));
+        assert_eq!(Node::Float(5.0).as_i64_exact(), None);
+        assert_eq!(Node::Float(5.5).as_f64_exact(), Some(5.5));
+        assert_eq!(Node::Int(5).as_f64_exact(), Some(5.0));
+
+        assert_eq!(Node::Int(5).try_as_i64(), Ok(5));
+        assert_eq!(Node::Float(5.0).try_as_i64(), Ok(5));
+        assert_eq!(Node::Float(5.5).try_as_i64(), Err(NumberError::Fractional));
+        assert_eq!(
+            Node::Float(f64::NAN).try_as_i64(),
+            Err(NumberError::OutOfRange)
+        );
+        assert_eq!(
+            Node::Float(1e300).try_as_i64(),
+            Err(NumberError::OutOfRange)
+        );
+        assert_eq!(
+            Node::Null.try_as_i64(),
+            Err(NumberError::NotANumber { found: "null" })
+        );
+    }
+
+    #[test]
+    fn it_measures_strings() {
+        let ascii = Node::Str("hello".into());
+        assert_eq!(ascii.str_char_count(), Some(5));
+        assert_eq!(ascii.str_byte_len(), Some(5));
+
+        let multibyte = Node::Str("héllo 🇱🇺".into());
+        assert_eq!(multibyte.str_char_count(), Some(8));
+        assert_eq!(multibyte.str_byte_len(), Some(15));
+
+        assert_eq!(Node::Int(12345).str_char_count(), None);
+        assert_eq!(Node::Null.str_byte_len(), None);
+    }
+
+    #[test]
+    fn it_round_trips_duplicate_keys() {
+        let input = "{\"a\":1,\"b\":0,\"a\":2}";
+        let node = parse(input).unwrap();
+        assert_eq!(node.to_string(), input);
+        assert!(node.has_duplicate_keys());
+    }
+
+    #[test]
+    fn it_detects_nested_duplicate_keys() {
+        assert!(!parse("{\"a\": 1, \"b\": {\"a\": 2}}")
+            .unwrap()
+            .has_duplicate_keys());
+        assert!(parse("[0, {\"b\": {\"x\": 1, \"x\": 1}}]")
+            .unwrap()
+            .has_duplicate_keys());
+        assert!(!Node::Array(vec![]).has_duplicate_keys());
+    }
+
+    #[test]
+    fn it_dedups_keys() {
+        let mut node = parse("{\"a\": 1, \"b\": 0, \"a\": 2}").unwrap();
+        node.dedup_keys(DedupStrategy::FirstWins);
+        assert_eq!(node, parse("{\"a\": 1, \"b\": 0}").unwrap());
+
+        let mut node = parse("{\"a\": 1, \"b\": 0, \"a\": 2}").unwrap();
+        node.dedup_keys(DedupStrategy::LastWins);
+        assert_eq!(node, parse("{\"a\": 2, \"b\": 0}").unwrap());
+    }
+
+    #[test]
+    fn it_dedups_nested_keys() {
+        let input = "[{\"a\": {\"x\": 1, \"x\": 2}, \"a\": {\"y\": 3, \"y\": 4}}]";
+
+        let mut node = parse(input).unwrap();
+        node.dedup_keys(DedupStrategy::FirstWins);
+        assert_eq!(node, parse("[{\"a\": {\"x\": 1}}]").unwrap());
+
+        let mut node = parse(input).unwrap();
+        node.dedup_keys(DedupStrategy::LastWins);
+        assert_eq!(node, parse("[{\"a\": {\"y\": 4}}]").unwrap());
+    }
+
+    #[test]
+    fn it_applies_defaults() {
+        let mut node =
+            parse("{\"port\": 8080, \"tls\": {\"on\": true}, \"hosts\": [\"a\"], \"name\": null}")
+                .unwrap();
+        let defaults = parse("{\"port\": 80, \"tls\": {\"on\": false, \"cert\": \"x.pem\"}, \"hosts\": [\"b\", \"c\"], \"name\": \"app\", \"debug\": false}").unwrap();
+        node.apply_defaults(&defaults);
+        let expected = "{\"port\": 8080, \"tls\": {\"on\": true, \"cert\": \"x.pem\"}, \"hosts\": [\"a\"], \"name\": null, \"debug\": false}";
+        assert_eq!(node, parse(expected).unwrap());
+
+        let mut node = Node::Int(1);
+        node.apply_defaults(&defaults);
+        assert_eq!(node, Node::Int(1));
+    }
+
+    #[test]
+    fn it_reports_array_element_types() {
+        let element_type = |input| parse(input).unwrap().array_element_type();
+        assert_eq!(
+            element_type("[1, 2, 3.5]"),
+            Some(ArrayElementType::Uniform("number"))
+        );
+        assert_eq!(
+            element_type("[true, false]"),
+            Some(ArrayElementType::Uniform("boolean"))
+        );
+        assert_eq!(element_type("[]"), Some(ArrayElementType::Empty));
+        assert_eq!(element_type("[1, \"a\"]"), Some(ArrayElementType::Mixed));
+        assert_eq!(element_type("{}"), None);
+    }
+
+    #[test]
+    fn it_compares_approximately() {
+        let sum = Node::Float(0.1 + 0.2);
+        assert_ne!(sum, Node::Float(0.3));
+        assert!(sum.approx_eq(&Node::Float(0.3), 1e-9));
+        assert!(!sum.approx_eq(&Node::Float(0.31), 1e-9));
+        assert!(Node::Float(2.0000000001).approx_eq(&Node::Int(2), 1e-9));
+
+        let a = parse("{\"x\": [0.30000000000000004, \"s\"], \"y\": null}").unwrap();
+        let b = parse("{\"x\": [0.3, \"s\"], \"y\": null}").unwrap();
+        assert!(a.approx_eq(&b, 1e-9));
+        let c = parse("{\"y\": null, \"x\": [0.3, \"s\"]}").unwrap();
+        assert!(!a.approx_eq(&c, 1e-9));
+    }
+
+    #[test]
+    fn it_estimates_memory_size() {
+        let scalar = Node::Null;
+        let small = parse("{\"a\": \"b\"}").unwrap();
+        let large = parse(SAMPLE).unwrap();
+        assert_eq!(scalar.memory_size(), std::mem::size_of::<Node>());
+        assert!(scalar.memory_size() < small.memory_size());
+        assert!(small.memory_size() < large.memory_size());
+    }
+
+    #[test]
+    fn it_lists_keys_and_array_lengths() {
+        let node = parse("{\"b\": [1, 2, 3], \"a\": [], \"b\": null}").unwrap();
+        assert_eq!(node.keys(), Some(vec!["b", "a", "b"]));
+        assert_eq!(node.array_len(), None);
+        assert_eq!(node.get("b").unwrap().array_len(), Some(3));
+        assert_eq!(node.get("a").unwrap().array_len(), Some(0));
+        assert_eq!(node.get("a").unwrap().keys(), None);
+    }
+
+    #[test]
+    fn it_checks_subsets() {
+        let response = parse(SAMPLE).unwrap();
+        let expected =
+            parse("[{\"name\": \"Adrien\", \"age\": 23}, {\"girlfriend\": null}]").unwrap();
+        assert!(expected.is_subset_of(&response));
+        assert!(!response.is_subset_of(&expected));
+        assert!(parse("[{}, {}]").unwrap().is_subset_of(&response));
+
+        let wrong_value = parse("[{\"age\": 24}, {}]").unwrap();
+        assert!(!wrong_value.is_subset_of(&response));
+        let missing_key = parse("[{\"id\": 1}, {}]").unwrap();
+        assert!(!missing_key.is_subset_of(&response));
+        let fewer_elements = parse("[{}]").unwrap();
+        assert!(!fewer_elements.is_subset_of(&response));
+    }
+
+    #[test]
+    fn it_searches_nested_values() {
+        let node = parse("{\"status\": [1, {\"error\": \"timeout\"}]}").unwrap();
+        assert!(node.deep_contains_str("timeout"));
+        assert!(node.deep_contains(&Node::Str("timeout".into())));
+        assert!(node.deep_contains(&parse("{\"error\": \"timeout\"}").unwrap()));
+        assert!(node.deep_contains(&Node::Int(1)));
+
+        assert!(!node.deep_contains_str("error"));
+        assert!(!node.deep_contains(&Node::Int(2)));
+        assert!(!node.deep_contains(&Node::Float(1.0)));
+
+        let node = Node::Array(vec![Node::Float(f64::NAN), Node::Float(0.0)]);
+        assert!(node.deep_contains(&Node::Float(f64::NAN)));
+        assert!(!node.deep_contains(&Node::Float(-0.0)));
+    }
+
+    #[test]
+    fn it_merges_layers_in_order() {
+        let defaults = parse(
+            "{\"port\": 80, \"log\": {\"level\": \"info\", \"color\": true}, \"hosts\": [\"a\"]}",
+        )
+        .unwrap();
+        let env = parse("{\"log\": {\"level\": \"debug\"}, \"hosts\": [\"b\", \"c\"]}").unwrap();
+        let overrides =
+            parse("{\"port\": 8080, \"log\": {\"level\": \"warn\"}, \"tls\": false}").unwrap();
+        let merged = Node::merge_all([defaults, env, overrides]);
+        let expected = parse(
+            "{\"port\": 8080, \"log\": {\"level\": \"warn\", \"color\": true}, \"hosts\": [\"b\", \"c\"], \"tls\": false}",
+        )
+        .unwrap();
+        assert_eq!(merged, expected);
+        assert_eq!(Node::merge_all([]), Node::Null);
+    }
+
+    #[test]
+    fn it_edits_members_in_place() {
+        let mut node = parse("{\"a\": 1, \"b\": \"x\", \"c\": {\"d\": 2}, \"e\": -3}").unwrap();
+        for (_, value) in node.entries_mut().unwrap() {
+            if let Node::Int(i) = value {
+                *i *= 2;
+            }
+        }
+        let expected = parse("{\"a\": 2, \"b\": \"x\", \"c\": {\"d\": 2}, \"e\": -6}").unwrap();
+        assert_eq!(node, expected);
+        assert!(node.elements_mut().is_none());
+
+        let mut node = parse("[1, 2]").unwrap();
+        node.elements_mut().unwrap().for_each(|e| *e = Node::Null);
+        assert_eq!(node, parse("[null, null]").unwrap());
+        assert!(node.entries_mut().is_none());
+    }
+
+    #[test]
+    fn it_preallocates_containers() {
+        match Node::object_with_capacity(8) {
+            Node::Object(members) => assert!(members.is_empty() && members.capacity() >= 8),
+            node => panic!("expected an object, got {:?}", node),
+        }
+        let mut node = Node::array_with_capacity(4);
+        match &node {
+            Node::Array(elements) => assert!(elements.is_empty() && elements.capacity() >= 4),
+            node => panic!("expected an array, got {:?}", node),
+        }
+        node.push(Node::Null).unwrap();
+        assert_eq!(node, parse("[null]").unwrap());
+    }
+
+    #[test]
+    fn it_builds_objects_without_duplicates() {
+        let node = Node::try_object([("a".into(), Node::Int(1)), ("b".into(), Node::Null)]);
+        assert_eq!(node, Ok(parse("{\"a\": 1, \"b\": null}").unwrap()));
+
+        let node = Node::try_object(vec![
+            ("a".into(), Node::Int(1)),
+            ("b".into(), Node::Null),
+            ("a".into(), Node::Int(2)),
+        ]);
+        assert_eq!(node, Err(BuildError::DuplicateKey("a".into())));
+    }
+
+    #[test]
+    fn it_gets_keys_case_insensitively() {
+        let node =
+            parse("{\"content-type\": \"text/html\", \"CONTENT-TYPE\": \"x\", \"é\": 1}").unwrap();
+        assert_eq!(node.get("Content-Type"), None);
+        assert_eq!(
+            node.get_ci("Content-Type"),
+            Some(&Node::Str("text/html".into()))
+        );
+        assert_eq!(node.get_ci("É"), None);
+        assert_eq!(Node::Null.get_ci("a"), None);
+    }
+}
@@ -0,0 +1,258 @@
+use alloc::{borrow::ToOwned, boxed::Box, string::String, vec::Vec};
+
+use crate::ast::{Node, NumberError};
+
+/// Types that can be decoded from a `Node`.
+///
+/// Implemented for the scalar types, `Option` (from `Null` or the inner
+/// type), `Vec` (from an array) and `Node` itself. Structs can implement it
+/// with `Node::get_typed`, whose errors convert into `DecodeError` with `?`.
+pub trait FromNode: Sized {
+    fn from_node(node: &Node) -> Result<Self, DecodeError>;
+}
+
+/// Error returned when a node can't be decoded into the requested type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The node isn't of the JSON type the target type is decoded from.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// A number isn't integral or doesn't fit in the target integer type.
+    Number(NumberError),
+    /// An object has no member with this key.
+    MissingKey(String),
+    /// Decoding the value of the member with this key failed.
+    Member {
+        key: String,
+        error: Box<DecodeError>,
+    },
+    /// Decoding the element at this index failed.
+    Element {
+        index: usize,
+        error: Box<DecodeError>,
+    },
+}
+
+/// Error returned by `Node::get_typed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GetError {
+    /// The node isn't an object.
+    NotAnObject { found: &'static str },
+    /// The object has no member with this key.
+    MissingKey(String),
+    /// The member was found but decoding its value failed.
+    Decode { key: String, error: DecodeError },
+}
+
+impl From<GetError> for DecodeError {
+    fn from(err: GetError) -> Self {
+        match err {
+            GetError::NotAnObject { found } => DecodeError::TypeMismatch {
+                expected: "object",
+                found,
+            },
+            GetError::MissingKey(key) => DecodeError::MissingKey(key),
+            GetError::Decode { key, error } => DecodeError::Member {
+                key,
+                error: Box::new(error),
+            },
+        }
+    }
+}
+
+impl Node {
+    /// Decodes the value of the first member with the given key into `T`,
+    /// e.g. `let port: u16 = config.get_typed("port")?`.
+    ///
+    /// A missing key is an error even if `T` is an `Option`, only a `null`
+    /// value decodes to `None`.
+    pub fn get_typed<T: FromNode>(&self, key: &str) -> Result<T, GetError> {
+        if !matches!(self, Node::Object(_)) {
+            return Err(GetError::NotAnObject {
+                found: self.type_name(),
+            });
+        }
+        let value = self
+            .get(key)
+            .ok_or_else(|| GetError::MissingKey(key.to_owned()))?;
+        T::from_node(value).map_err(|error| GetError::Decode {
+            key: key.to_owned(),
+            error,
+        })
+    }
+}
+
+fn type_mismatch(expected: &'static str, node: &Node) -> DecodeError {
+    DecodeError::TypeMismatch {
+        expected,
+        found: node.type_name(),
+    }
+}
+
+impl FromNode for Node {
+    fn from_node(node: &Node) -> Result<Self, DecodeError> {
+        Ok(node.clone())
+    }
+}
+
+impl FromNode for bool {
+    fn from_node(node: &Node) -> Result<Self, DecodeError> {
+        match node {
+            Node::True => Ok(true),
+            Node::False => Ok(false),
+            _ => Err(type_mismatch("boolean", node)),
+        }
+    }
+}
+
+impl FromNode for String {
+    fn from_node(node: &Node) -> Result<Self, DecodeError> {
+        match node {
+            Node::Str(s) => Ok(s.clone()),
+            _ => Err(type_mismatch("string", node)),
+        }
+    }
+}
+
+impl FromNode for f64 {
+    fn from_node(node: &Node) -> Result<Self, DecodeError> {
+        match node {
+            Node::Int(i) => Ok(*i as f64),
+            Node::Float(f) => Ok(*f),
+            _ => Err(type_mismatch("number", node)),
+        }
+    }
+}
+
+/// Integers are decoded from an `Int` or an integral `Float`, like
+/// `Node::try_as_i64`.
+macro_rules! impl_from_node_for_int {
+    ($($ty:ty),*) => {
+        $(
+            impl FromNode for $ty {
+                fn from_node(node: &Node) -> Result<Self, DecodeError> {
+                    let i = node.try_as_i64().map_err(|err| match err {
+                        NumberError::NotANumber { found } => DecodeError::TypeMismatch {
+                            expected: "number",
+                            found,
+                        },
+                        err => DecodeError::Number(err),
+                    })?;
+                    <$ty>::try_from(i).map_err(|_| DecodeError::Number(NumberError::OutOfRange))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_node_for_int!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: FromNode> FromNode for Option<T> {
+    fn from_node(node: &Node) -> Result<Self, DecodeError> {
+        match node {
+            Node::Null => Ok(None),
+            _ => T::from_node(node).map(Some),
+        }
+    }
+}
+
+impl<T: FromNode> FromNode for Vec<T> {
+    fn from_node(node: &Node) -> Result<Self, DecodeError> {
+        match node {
+            Node::Array(elements) => elements
+                .iter()
+                .enumerate()
+                .map(|(index, element)| {
+                    T::from_node(element).map_err(|error| DecodeError::Element {
+                        index,
+                        error: Box::new(error),
+                    })
+                })
+                .collect(),
+            _ => Err(type_mismatch("array", node)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DecodeError, FromNode, GetError};
+    use crate::ast::{Node, NumberError};
+    use crate::parser::parse;
+
+    #[derive(Debug, PartialEq)]
+    struct Server {
+        host: String,
+        port: u16,
+        tags: Vec<String>,
+        timeout: Option<f64>,
+    }
+
+    impl FromNode for Server {
+        fn from_node(node: &Node) -> Result<Self, DecodeError> {
+            Ok(Server {
+                host: node.get_typed("host")?,
+                port: node.get_typed("port")?,
+                tags: node.get_typed("tags")?,
+                timeout: node.get_typed("timeout")?,
+            })
+        }
+    }
+
+    const CONFIG: &str = "{\"retries\": 3, \"server\": {\"host\": \"localhost\", \"port\": 8080, \"tags\": [\"a\"], \"timeout\": null}}";
+
+    #[test]
+    fn it_gets_typed_values() {
+        let config = parse(CONFIG).unwrap();
+        let retries: u8 = config.get_typed("retries").unwrap();
+        assert_eq!(retries, 3);
+        let server: Server = config.get_typed("server").unwrap();
+        let expected = Server {
+            host: "localhost".into(),
+            port: 8080,
+            tags: vec!["a".into()],
+            timeout: None,
+        };
+        assert_eq!(server, expected);
+    }
+
+    #[test]
+    fn it_tells_missing_keys_from_decode_errors() {
+        let config = parse(CONFIG).unwrap();
+        assert_eq!(
+            config.get_typed::<u8>("port"),
+            Err(GetError::MissingKey("port".into()))
+        );
+        assert_eq!(
+            config.get_typed::<String>("retries"),
+            Err(GetError::Decode {
+                key: "retries".into(),
+                error: DecodeError::TypeMismatch {
+                    expected: "string",
+                    found: "number"
+                },
+            })
+        );
+        assert_eq!(
+            Node::Null.get_typed::<u8>("port"),
+            Err(GetError::NotAnObject { found: "null" })
+        );
+    }
+
+    #[test]
+    fn it_reports_nested_decode_errors() {
+        let config =
+            parse("{\"server\": {\"host\": \"h\", \"port\": 70000, \"tags\": []}}").unwrap();
+        let err = config.get_typed::<Server>("server").unwrap_err();
+        let expected = GetError::Decode {
+            key: "server".into(),
+            error: DecodeError::Member {
+                key: "port".into(),
+                error: Box::new(DecodeError::Number(NumberError::OutOfRange)),
+            },
+        };
+        assert_eq!(err, expected);
+    }
+}
@@ -0,0 +1,99 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::ast::Node;
+use crate::parser::{parse, ParseError};
+
+/// Error returned when reading and parsing JSON from a reader or file.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading failed, including when the input isn't valid UTF-8.
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl From<io::Error> for Error {
+    fn from(other: io::Error) -> Self {
+        Error::Io(other)
+    }
+}
+
+impl From<ParseError> for Error {
+    fn from(other: ParseError) -> Self {
+        Error::Parse(other)
+    }
+}
+
+/// Reads `reader` to the end and parses its contents.
+pub fn parse_reader<R: Read>(mut reader: R) -> Result<Node, Error> {
+    let mut input = String::new();
+    reader.read_to_string(&mut input)?;
+    Ok(parse(&input)?)
+}
+
+/// Reads and parses the file at `path`.
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<Node, Error> {
+    parse_reader(File::open(path)?)
+}
+
+impl Node {
+    /// Writes this node as compact JSON text to the file at `path`, creating
+    /// or truncating it.
+    pub fn write_file<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.to_writer(&mut writer)?;
+        writer.flush()
+    }
+
+    /// Writes this node as pretty printed JSON text to the file at `path`,
+    /// creating or truncating it.
+    pub fn write_file_pretty<P: AsRef<Path>>(&self, path: P, indent: usize) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.to_writer_pretty(&mut writer, indent)?;
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::lexer::TokenKind;
+    use crate::parser::ParseErrorKind;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("jason-{}-{}.json", std::process::id(), name))
+    }
+
+    #[test]
+    fn it_round_trips_through_a_file() {
+        let node = parse("{\"name\": \"Adrien\", \"tags\": [1, 2.5, null]}").unwrap();
+
+        let path = temp_path("compact");
+        node.write_file(&path).unwrap();
+        assert_eq!(read_file(&path).unwrap(), node);
+        std::fs::remove_file(&path).unwrap();
+
+        let path = temp_path("pretty");
+        node.write_file_pretty(&path, 2).unwrap();
+        assert_eq!(read_file(&path).unwrap(), node);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_reports_parse_errors_from_a_reader() {
+        match parse_reader("[1,".as_bytes()) {
+            Err(Error::Parse(err)) => {
+                assert_eq!(err.kind, ParseErrorKind::ExpectedValue(TokenKind::Eof))
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn it_reports_io_errors() {
+        assert!(matches!(read_file(temp_path("missing")), Err(Error::Io(_))));
+    }
+}
@@ -0,0 +1,197 @@
+use alloc::{collections::BTreeSet, rc::Rc, string::String, vec::Vec};
+use core::mem;
+
+use crate::ast::Node;
+use crate::parser::{parse_with_handler, Handler, ParseOptions, ParseResult};
+
+/// A JSON value whose object keys are shared rather than owned, so a key
+/// repeated across many objects, e.g. in an array of records, is stored
+/// once.
+#[derive(Debug, Clone, PartialEq)]
+pub enum InternedNode {
+    Object(Vec<(Rc<str>, InternedNode)>),
+    Array(Vec<InternedNode>),
+    /// Any node but an object or an array.
+    Scalar(Node),
+}
+
+/// A pool of object keys, and the `Handler` building an `InternedNode` with
+/// them.
+///
+/// Reusing an interner across documents shares keys between them too.
+#[derive(Debug, Default)]
+pub struct KeyInterner {
+    keys: BTreeSet<Rc<str>>,
+}
+
+impl KeyInterner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pooled copy of `key`, added to the pool if it isn't there yet.
+    pub fn intern(&mut self, key: &str) -> Rc<str> {
+        if let Some(interned) = self.keys.get(key) {
+            return interned.clone();
+        }
+        let interned: Rc<str> = key.into();
+        self.keys.insert(interned.clone());
+        interned
+    }
+
+    /// Number of distinct keys in the pool.
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl Handler for KeyInterner {
+    type Key = Rc<str>;
+    type Value = InternedNode;
+
+    fn key(&mut self, key: String) -> Rc<str> {
+        self.intern(&key)
+    }
+
+    fn scalar(&mut self, node: Node) -> InternedNode {
+        InternedNode::Scalar(node)
+    }
+
+    fn object(&mut self, members: Vec<(Rc<str>, InternedNode)>) -> InternedNode {
+        InternedNode::Object(members)
+    }
+
+    fn array(&mut self, elements: Vec<InternedNode>) -> InternedNode {
+        InternedNode::Array(elements)
+    }
+}
+
+/// Parses `input` like `parse`, interning object keys as they are read so no
+/// `Node` tree with a copy of every key is ever built.
+pub fn parse_interned(input: &str) -> ParseResult<InternedNode> {
+    parse_with_handler(input, &ParseOptions::default(), &mut KeyInterner::new())
+}
+
+impl Node {
+    /// Copies this tree with its object keys interned in `interner`.
+    pub fn intern_keys(&self, interner: &mut KeyInterner) -> InternedNode {
+        match self {
+            Node::Object(members) => InternedNode::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (interner.intern(key), value.intern_keys(interner)))
+                    .collect(),
+            ),
+            Node::Array(elements) => InternedNode::Array(
+                elements
+                    .iter()
+                    .map(|element| element.intern_keys(interner))
+                    .collect(),
+            ),
+            scalar => InternedNode::Scalar(scalar.clone()),
+        }
+    }
+}
+
+impl InternedNode {
+    /// Copies this tree into a `Node`, with a `String` per key.
+    pub fn to_node(&self) -> Node {
+        match self {
+            InternedNode::Object(members) => Node::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (String::from(&**key), value.to_node()))
+                    .collect(),
+            ),
+            InternedNode::Array(elements) => {
+                Node::Array(elements.iter().map(InternedNode::to_node).collect())
+            }
+            InternedNode::Scalar(node) => node.clone(),
+        }
+    }
+
+    /// Estimates the bytes of memory used by this tree, like
+    /// `Node::memory_size`, counting each shared key once.
+    pub fn memory_size(&self) -> usize {
+        let mut keys = BTreeSet::new();
+        mem::size_of::<InternedNode>() + self.heap_size(&mut keys)
+    }
+
+    /// Bytes allocated on the heap by this node and its descendants, except
+    /// for keys already in `keys`.
+    fn heap_size(&self, keys: &mut BTreeSet<*const u8>) -> usize {
+        match self {
+            InternedNode::Object(members) => {
+                let children: usize = members
+                    .iter()
+                    .map(|(key, value)| {
+                        // An `Rc` allocation holds the strong and weak counts
+                        // before the key.
+                        let key_size = if keys.insert(key.as_ptr()) {
+                            2 * mem::size_of::<usize>() + key.len()
+                        } else {
+                            0
+                        };
+                        key_size + value.heap_size(keys)
+                    })
+                    .sum();
+                members.capacity() * mem::size_of::<(Rc<str>, InternedNode)>() + children
+            }
+            InternedNode::Array(elements) => {
+                let children: usize = elements.iter().map(|e| e.heap_size(keys)).sum();
+                elements.capacity() * mem::size_of::<InternedNode>() + children
+            }
+            InternedNode::Scalar(node) => node.heap_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::rc::Rc;
+
+    use super::{parse_interned, InternedNode, KeyInterner};
+    use crate::parser::parse;
+
+    fn records(n: usize) -> String {
+        let record = "{\"identifier\": 1, \"description\": \"x\", \"enabled\": true}";
+        format!("[{}]", vec![record; n].join(","))
+    }
+
+    #[test]
+    fn it_shares_identical_keys() {
+        let node = parse_interned(&records(2)).unwrap();
+        let keys: Vec<&Rc<str>> = match &node {
+            InternedNode::Array(elements) => elements
+                .iter()
+                .flat_map(|element| match element {
+                    InternedNode::Object(members) => members.iter().map(|(key, _)| key),
+                    _ => unreachable!(),
+                })
+                .collect(),
+            _ => unreachable!(),
+        };
+        assert_eq!(keys.len(), 6);
+        assert!(Rc::ptr_eq(keys[0], keys[3]));
+        assert!(Rc::ptr_eq(keys[1], keys[4]));
+        assert!(!Rc::ptr_eq(keys[0], keys[1]));
+        assert_eq!(node.to_node(), parse(&records(2)).unwrap());
+    }
+
+    #[test]
+    fn it_reduces_memory_for_repeated_keys() {
+        let node = parse(&records(100)).unwrap();
+        let mut interner = KeyInterner::new();
+        let interned = node.intern_keys(&mut interner);
+        assert_eq!(interner.len(), 3);
+        assert_eq!(interned, parse_interned(&records(100)).unwrap());
+
+        let saved = node.memory_size() - interned.memory_size();
+        // Every record but the first saves its 28 bytes of keys.
+        assert!(saved >= 99 * 28, "saved {} bytes", saved);
+    }
+}
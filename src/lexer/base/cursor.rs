@@ -1,4 +1,4 @@
-use std::str::Chars;
+use core::str::Chars;
 
 /// Peekable iterator over a char sequence.
 ///
@@ -8,6 +8,8 @@ pub struct Cursor<'a> {
     len_remaining: usize,
     /// Iterator over chars. Slightly faster than a &str.
     chars: Chars<'a>,
+    /// Length in bytes past which a whitespace token stops being scanned.
+    pub(crate) max_whitespace_run: Option<usize>,
     #[cfg(debug_assertions)]
     prev: char,
 }
@@ -19,6 +21,8 @@ impl<'a> Cursor<'a> {
         Self {
             len_remaining: input.len(),
             chars: input.chars(),
+            max_whitespace_run: None,
+            #[cfg(debug_assertions)]
             prev: EOF_CHAR,
         }
     }
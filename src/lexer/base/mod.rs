@@ -68,7 +68,7 @@ impl Cursor<'_> {
                         let c = self.bump().unwrap();
                         self.number(c)
                     }
-                    _ => return Token::new(Unknown, 1),
+                    _ => Unknown,
                 }
             }
 
@@ -94,7 +94,12 @@ impl Cursor<'_> {
 
     fn whitespace(&mut self) -> TokenKind {
         debug_assert!(self.prev().is_whitespace());
-        self.eat_while(|ch| ch.is_whitespace());
+        // A run past the limit is rejected whatever its length, so stop there
+        // rather than scanning all of it.
+        let max = self.max_whitespace_run.unwrap_or(usize::MAX);
+        while self.pos_within_token() <= max && self.first().is_whitespace() && !self.is_eof() {
+            self.bump();
+        }
         Whitespace
     }
 
@@ -109,16 +114,10 @@ impl Cursor<'_> {
 
         if first_digit == '0' {
             let has_digits = match self.first() {
-                '.' => match self.second() {
-                    '0'..='9' => true,
-                    _ => false,
-                },
+                '.' => self.second().is_ascii_digit(),
                 'e' | 'E' => match self.second() {
                     '0'..='9' => true,
-                    '+' | '-' => match self.third() {
-                        '0'..='9' => true,
-                        _ => false,
-                    },
+                    '+' | '-' => self.third().is_ascii_digit(),
                     _ => false,
                 },
                 _ => false,
@@ -200,14 +199,9 @@ impl Cursor<'_> {
 
     fn eat_decimal_digits(&mut self) -> bool {
         let mut has_digits = false;
-        loop {
-            match self.first() {
-                '0'..='9' => {
-                    has_digits = true;
-                    self.bump();
-                }
-                _ => break,
-            }
+        while let '0'..='9' = self.first() {
+            has_digits = true;
+            self.bump();
         }
         has_digits
     }
@@ -222,7 +216,7 @@ macro_rules! tokenize_test {
         fn $name() {
             pub fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
                 let mut cursor = Cursor::new(input);
-                std::iter::from_fn(move || {
+                core::iter::from_fn(move || {
                     let token = cursor.advance_token();
                     if token.kind != Eof {
                         Some(token)
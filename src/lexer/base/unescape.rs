@@ -1,7 +1,9 @@
-use std::{ops::Range, str::Chars};
+use alloc::string::String;
+use core::{ops::Range, str::Chars};
 
 /// Errors and warnings that can occur during string unescaping.
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum EscapeError {
     /// Escaped '\' character without continuation. (Unterminated?)
     LoneSlash,
@@ -23,13 +25,27 @@ pub enum EscapeError {
     EscapeOnlyChar,
     /// Bad unicode escape
     BadUnicodeEscape,
+    /// Bad hex escape, e.g. '\xZZ'.
+    BadHexEscape,
     /// Invalid in-bound unicode character code, e.g. '\u{DFFF}'.
     LoneSurrogateUnicodeEscape,
     /// Out of bounds unicode character code, e.g. '\u{FFFFFF}'.
     OutOfRangeUnicodeEscape,
 }
 
-fn scan_escape(chars: &mut Chars<'_>) -> Result<char, EscapeError> {
+/// Non-standard escapes that can be enabled on top of the JSON spec.
+///
+/// All of them are disabled by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EscapeOptions {
+    /// Allow JSON5 '\xHH' escapes with exactly two hex digits.
+    pub hex_escapes: bool,
+    /// Allow JSON5 line continuations, a '\' immediately followed by '\n' or
+    /// '\r\n'. Both the backslash and the line terminator are dropped.
+    pub line_continuations: bool,
+}
+
+fn scan_escape(chars: &mut Chars<'_>, options: &EscapeOptions) -> Result<char, EscapeError> {
     // Previous character was '\\', unescape what follows.
     let res = match chars.next().ok_or(EscapeError::LoneSlash)? {
         '"' => '"',
@@ -60,22 +76,33 @@ fn scan_escape(chars: &mut Chars<'_>) -> Result<char, EscapeError> {
                     Some(c) => {
                         let digit = c.to_digit(16).ok_or(EscapeError::BadUnicodeEscape)?;
                         n_digits += 1;
-                        let digit = digit as u32;
                         value = value * 16 + digit;
                         if n_digits < 4 {
                             continue;
                         }
-                        break std::char::from_u32(value).ok_or_else(|| {
-                            if value > 0x10FFFF {
-                                EscapeError::OutOfRangeUnicodeEscape
-                            } else {
-                                EscapeError::LoneSurrogateUnicodeEscape
-                            }
+                        break core::char::from_u32(value).ok_or(if value > 0x10FFFF {
+                            EscapeError::OutOfRangeUnicodeEscape
+                        } else {
+                            EscapeError::LoneSurrogateUnicodeEscape
                         })?;
                     }
                 };
             }
         }
+        'x' if options.hex_escapes => {
+            // We've parsed '\x', now we have to parse exactly two hex digits.
+            let mut value = 0;
+            for _ in 0..2 {
+                let digit = chars
+                    .next()
+                    .and_then(|c| c.to_digit(16))
+                    .ok_or(EscapeError::BadHexEscape)?;
+                value = value * 16 + digit;
+            }
+            // correctness: two hex digits are at most 0xFF, always a valid char.
+            core::char::from_u32(value).unwrap()
+        }
+
         _ => return Err(EscapeError::InvalidEscape),
     };
     Ok(res)
@@ -83,30 +110,58 @@ fn scan_escape(chars: &mut Chars<'_>) -> Result<char, EscapeError> {
 
 fn iter_unescape_string(
     input: &str,
+    options: EscapeOptions,
 ) -> impl Iterator<Item = (Range<usize>, Result<char, EscapeError>)> + '_ {
     let mut chars = input.chars();
-    std::iter::from_fn(move || {
-        if let Some(c) = chars.next() {
-            let start = input.len() - chars.as_str().len() - c.len_utf8();
-            let res = match c {
-                '\\' => scan_escape(&mut chars),
-                '"' => Err(EscapeError::EscapeOnlyChar),
-                '\u{0008}' => Err(EscapeError::BareBackspace),
-                '\u{000C}' => Err(EscapeError::BareFormFeed),
-                '\n' => Err(EscapeError::BareLineFeed),
-                '\r' => Err(EscapeError::BareCarriageReturn),
-                '\t' => Err(EscapeError::BareHorizontalTab),
-                c if c.is_control() => Err(EscapeError::BadControlChar),
-                _ => Ok(c),
-            };
-            let end = input.len() - chars.as_str().len();
-            Some((start..end, res))
-        } else {
-            None
-        }
+    core::iter::from_fn(move || loop {
+        let c = chars.next()?;
+        let start = input.len() - chars.as_str().len() - c.len_utf8();
+        let res = match c {
+            '\\' if options.line_continuations && skip_line_terminator(&mut chars) => {
+                continue;
+            }
+            '\\' => scan_escape(&mut chars, &options),
+            '"' => Err(EscapeError::EscapeOnlyChar),
+            '\u{0008}' => Err(EscapeError::BareBackspace),
+            '\u{000C}' => Err(EscapeError::BareFormFeed),
+            '\n' => Err(EscapeError::BareLineFeed),
+            '\r' => Err(EscapeError::BareCarriageReturn),
+            '\t' => Err(EscapeError::BareHorizontalTab),
+            c if c.is_control() => Err(EscapeError::BadControlChar),
+            _ => Ok(c),
+        };
+        let end = input.len() - chars.as_str().len();
+        return Some((start..end, res));
     })
 }
 
+/// Consumes a '\n' or '\r\n' line terminator if `chars` starts with one.
+fn skip_line_terminator(chars: &mut Chars<'_>) -> bool {
+    let rest = chars.as_str();
+    let len = if rest.starts_with('\n') {
+        1
+    } else if rest.starts_with("\r\n") {
+        2
+    } else {
+        return false;
+    };
+    chars.nth(len - 1);
+    true
+}
+
+/// Unescapes the contents of a JSON string, without its surrounding quotes,
+/// one char at a time.
+///
+/// Every item holds the byte range of the source text it was decoded from,
+/// e.g. `0..6` for `\u0041`, and either the decoded char or the reason it
+/// couldn't be decoded. Unlike `unescape_string`, iteration carries on past
+/// errors so consumers can decide how to handle each one.
+pub fn unescape_chars(
+    input: &str,
+) -> impl Iterator<Item = (Range<usize>, Result<char, EscapeError>)> + '_ {
+    iter_unescape_string(input, EscapeOptions::default())
+}
+
 // pub fn unescape_string_with_cb<F>(input: &str, callback: &mut F)
 // where
 //     F: FnMut(Range<usize>, Result<char, EscapeError>),
@@ -117,8 +172,11 @@ fn iter_unescape_string(
 //     }
 // }
 
-pub fn unescape_string(input: &str) -> Result<String, (EscapeError, Range<usize>)> {
-    let result: Result<String, _> = iter_unescape_string(input)
+pub fn unescape_string(
+    input: &str,
+    options: &EscapeOptions,
+) -> Result<String, (EscapeError, Range<usize>)> {
+    let result: Result<String, _> = iter_unescape_string(input, *options)
         .map(|(range, res)| match res {
             Ok(c) => Ok(c),
             Err(e) => Err((e, range)),
@@ -130,24 +188,42 @@ pub fn unescape_string(input: &str) -> Result<String, (EscapeError, Range<usize>
 // Tests.
 
 macro_rules! unescape_test {
-    (FAIL: $name:ident, $input:expr, $should_be:expr) => {
+    (@options) => {
+        EscapeOptions::default()
+    };
+    (@options $options:expr) => {
+        $options
+    };
+    (FAIL: $name:ident, $input:expr, $should_be:expr $(, $options:expr)?) => {
         #[cfg(test)]
         #[test]
         fn $name() {
-            let output = unescape_string($input);
+            let output = unescape_string($input, &unescape_test!(@options $($options)?));
             assert_eq!(output, Err($should_be));
         }
     };
-    ($name:ident, $input:expr, $should_be:expr) => {
+    ($name:ident, $input:expr, $should_be:expr $(, $options:expr)?) => {
         #[cfg(test)]
         #[test]
         fn $name() {
-            let output = unescape_string($input);
+            let output = unescape_string($input, &unescape_test!(@options $($options)?));
             assert_eq!(output, Ok($should_be.into()));
         }
     };
 }
 
+#[cfg(test)]
+const HEX_ESCAPES: EscapeOptions = EscapeOptions {
+    hex_escapes: true,
+    line_continuations: false,
+};
+
+#[cfg(test)]
+const LINE_CONTINUATIONS: EscapeOptions = EscapeOptions {
+    hex_escapes: false,
+    line_continuations: true,
+};
+
 // Succeed.
 
 unescape_test!(it_unescapes_empty_string, "", "");
@@ -192,6 +268,20 @@ unescape_test!(
     "♥️"
 );
 
+unescape_test!(
+    it_unescapes_string_with_hex_escapes,
+    "\\x41\\x62\\xe9",
+    "Abé",
+    HEX_ESCAPES
+);
+
+unescape_test!(
+    it_unescapes_string_with_line_continuations,
+    "one \\\ntwo \\\r\nthree",
+    "one two three",
+    LINE_CONTINUATIONS
+);
+
 // Fail.
 
 unescape_test!(
@@ -260,3 +350,51 @@ unescape_test!(
     "\\uDFFF",
     (EscapeError::LoneSurrogateUnicodeEscape, 0..6)
 );
+
+unescape_test!(
+    FAIL: it_fails_unescape_with_hex_escape_in_strict_mode,
+    "\\x41",
+    (EscapeError::InvalidEscape, 0..2)
+);
+
+unescape_test!(
+    FAIL: it_fails_unescape_with_bad_hex_escape,
+    "\\x4",
+    (EscapeError::BadHexEscape, 0..3),
+    HEX_ESCAPES
+);
+
+unescape_test!(
+    FAIL: it_fails_unescape_with_non_hex_escape,
+    "\\xG1",
+    (EscapeError::BadHexEscape, 0..3),
+    HEX_ESCAPES
+);
+
+unescape_test!(
+    FAIL: it_fails_unescape_with_line_continuation_in_strict_mode,
+    "one \\\ntwo",
+    (EscapeError::InvalidEscape, 4..6)
+);
+
+unescape_test!(
+    FAIL: it_fails_unescape_with_lone_carriage_return_continuation,
+    "one \\\rtwo",
+    (EscapeError::InvalidEscape, 4..6),
+    LINE_CONTINUATIONS
+);
+
+#[cfg(test)]
+#[test]
+fn it_unescapes_chars_past_errors() {
+    let chars: alloc::vec::Vec<_> = unescape_chars("a\\zé\\n").collect();
+    assert_eq!(
+        chars,
+        [
+            (0..1, Ok('a')),
+            (1..3, Err(EscapeError::InvalidEscape)),
+            (3..5, Ok('é')),
+            (5..7, Ok('\n')),
+        ]
+    );
+}
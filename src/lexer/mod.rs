@@ -1,15 +1,53 @@
 mod base;
 pub mod token;
 
-use base::{unescape, Cursor};
-pub use token::{Span, StrError, Token, TokenKind};
+use alloc::borrow::ToOwned;
+
+use crate::ast::integral_f64_to_isize;
+
+pub use base::unescape::{unescape_chars, EscapeError, EscapeOptions};
+use base::{is_id_continue, unescape, Cursor};
+pub use token::{span_text, Span, StrError, Token, TokenKind};
+
+/// Options enabling non-standard syntax in the lexer.
+///
+/// The default options are strict.
+#[derive(Debug, Clone, Default)]
+pub struct LexOptions {
+    /// Non-standard string escapes to accept.
+    pub escapes: EscapeOptions,
+    /// Lex literals with an exponent but no fraction, like `1e3`, as `Int`s
+    /// when their value is integral and fits in an `isize`. By default every
+    /// literal with an exponent is a `Float`, so `1e3` serializes as `1000.0`.
+    pub integral_exponents_as_int: bool,
+    /// Lex numbers with leading zeros, like `007`, as a single `InvalidInt`
+    /// token. By default they are split into several number tokens, e.g.
+    /// `Int(0)`, `Int(0)` and `Int(7)`, since leading zeros aren't allowed.
+    pub strict_numbers: bool,
+    /// Lex JSON5's `Infinity` and `NaN`, optionally signed like `-Infinity`
+    /// or `+NaN`, as non-finite `Float`s.
+    pub non_finite_numbers: bool,
+    /// With `non_finite_numbers`, also accept `Infinity` and `NaN` spelled
+    /// in any case, e.g. `infinity` or `NAN`.
+    pub case_insensitive_literals: bool,
+    /// Skip a first line starting with `#` or `//`, such as a `#!` shebang
+    /// line, as if it were whitespace. The line must start at the very
+    /// beginning of the input.
+    pub skip_header_line: bool,
+    /// Maximum length in bytes of a run of whitespace between two tokens
+    /// before lexing a `WhitespaceRunTooLong` token in its place, so callers
+    /// can give up on inputs padded with huge amounts of whitespace. The
+    /// token ends at the char exceeding the limit, the rest of the run isn't
+    /// scanned. Unlimited when `None`.
+    pub max_whitespace_run: Option<usize>,
+}
 
 pub fn tokenize(input: &str) -> impl Iterator<Item = (Token, bool)> + '_ {
     let mut tokenizer = Tokenizer::new(input);
 
-    std::iter::from_fn(move || {
+    core::iter::from_fn(move || {
         let (token, whitespace) = tokenizer.next_token();
-        if token.kind != token::Eof {
+        if !token.kind.is_eof() {
             Some((token, whitespace))
         } else {
             None
@@ -17,32 +55,81 @@ pub fn tokenize(input: &str) -> impl Iterator<Item = (Token, bool)> + '_ {
     })
 }
 
+/// Cooked tokens paired with their byte spans in `input`, without the
+/// whitespace flag of `tokenize`.
+///
+/// Spans always start and end on char boundaries, so they can be used to
+/// slice `input` directly.
+pub fn spanned_tokens(input: &str) -> impl Iterator<Item = (TokenKind, Span)> + '_ {
+    tokenize(input).map(|(token, _)| (token.kind, token.span))
+}
+
 pub struct Tokenizer<'a> {
     pos: usize,
     input: &'a str,
     cursor: Cursor<'a>,
+    options: LexOptions,
 }
 
 impl<'a> Tokenizer<'a> {
     pub fn new(input: &'a str) -> Self {
+        Self::with_options(input, LexOptions::default())
+    }
+
+    pub fn with_options(input: &'a str, options: LexOptions) -> Self {
+        let pos = if options.skip_header_line {
+            header_line_len(input)
+        } else {
+            0
+        };
+        let mut cursor = Cursor::new(&input[pos..]);
+        cursor.max_whitespace_run = options.max_whitespace_run;
         Self {
-            pos: 0,
+            pos,
             input,
-            cursor: Cursor::new(&input),
+            cursor,
+            options,
+        }
+    }
+
+    /// Byte offset in the input of the next token `next_token` will return,
+    /// past any whitespace preceding it. At the end of the input, this is the
+    /// input's length.
+    ///
+    /// A run of whitespace longer than `LexOptions::max_whitespace_run` is
+    /// itself the next token, so this is where the run starts.
+    pub fn position(&self) -> usize {
+        let rest = &self.input[self.pos..];
+        let run = rest.len() - rest.trim_start_matches(char::is_whitespace).len();
+        match self.options.max_whitespace_run {
+            Some(max) if run > max => self.pos,
+            _ => self.pos + run,
         }
     }
 
     /// Returns the next token, paired with a bool indicating if the token was
     /// preceded by whitespace.
+    ///
+    /// Once the input is exhausted this returns an `Eof` token (see
+    /// `TokenKind::is_eof`) on every call. The `Iterator` impl returns `None`
+    /// instead.
     pub fn next_token(&mut self) -> (Token, bool) {
         let mut preceded_by_whitespace = false;
 
         loop {
             let token = self.cursor.advance_token();
             let start = self.pos;
-            self.pos = self.pos + token.len;
+            self.pos += token.len;
 
             let kind = match token.kind {
+                base::TokenKind::Whitespace
+                    if self
+                        .options
+                        .max_whitespace_run
+                        .is_some_and(|max| token.len > max) =>
+                {
+                    token::WhitespaceRunTooLong
+                }
                 // Whitespace: skip.
                 base::TokenKind::Whitespace => {
                     preceded_by_whitespace = true;
@@ -52,7 +139,12 @@ impl<'a> Tokenizer<'a> {
                 // Identifier.
                 base::TokenKind::Ident => self.cook_base_ident(start),
 
-                // Integer.
+                // Integer. Only a `0` can be directly followed by a digit.
+                base::TokenKind::Int
+                    if self.options.strict_numbers && self.cursor.first().is_ascii_digit() =>
+                {
+                    self.cook_leading_zeros(start)
+                }
                 base::TokenKind::Int => self.cook_base_integer(start),
 
                 // Float.
@@ -70,7 +162,10 @@ impl<'a> Tokenizer<'a> {
                 base::TokenKind::Colon => token::Colon,
                 base::TokenKind::Comma => token::Comma,
 
-                base::TokenKind::Unknown => self.cook_base_unknown(start),
+                base::TokenKind::Unknown => match self.cook_signed_non_finite(start) {
+                    Some(kind) => kind,
+                    None => self.cook_base_unknown(start),
+                },
                 base::TokenKind::Eof => token::Eof,
             };
             let span = Span::new(start, self.pos);
@@ -84,31 +179,97 @@ impl<'a> Tokenizer<'a> {
             "true" => token::True,
             "false" => token::False,
             "null" => token::Null,
-            ident => token::InvalidIdent(ident.to_owned()),
+            ident => match self.non_finite(ident) {
+                Some(value) => token::Float(value),
+                None => token::InvalidIdent(ident.to_owned()),
+            },
         }
     }
 
+    /// The value of `word` if it spells a non-finite float and those are
+    /// enabled.
+    fn non_finite(&self, word: &str) -> Option<f64> {
+        if !self.options.non_finite_numbers {
+            return None;
+        }
+        let spells = |keyword: &str| {
+            if self.options.case_insensitive_literals {
+                word.eq_ignore_ascii_case(keyword)
+            } else {
+                word == keyword
+            }
+        };
+        if spells("Infinity") {
+            Some(f64::INFINITY)
+        } else if spells("NaN") {
+            Some(f64::NAN)
+        } else {
+            None
+        }
+    }
+
+    /// Lexes a `-` or `+` directly followed by a non-finite float as a single
+    /// `Float` token. The base lexer has no such token so this looks ahead.
+    fn cook_signed_non_finite(&mut self, start: usize) -> Option<TokenKind> {
+        let sign = match self.str_from(start) {
+            "-" => -1.0,
+            "+" => 1.0,
+            _ => return None,
+        };
+        let rest = self.str_from_to(self.pos, self.input.len());
+        let len = rest.find(|c| !is_id_continue(c)).unwrap_or(rest.len());
+        let value = self.non_finite(&rest[..len])?;
+        let token = self.cursor.advance_token();
+        debug_assert_eq!(token.len, len);
+        self.pos += token.len;
+        Some(token::Float(sign * value))
+    }
+
     fn cook_base_integer(&self, start: usize) -> TokenKind {
         let slice = self.str_from(start);
         token::Int(slice.parse().unwrap())
     }
 
+    /// Eats the rest of a number with leading zeros, which the base lexer
+    /// splits into several number tokens.
+    fn cook_leading_zeros(&mut self, start: usize) -> TokenKind {
+        while self.cursor.first().is_ascii_digit() {
+            let token = self.cursor.advance_token();
+            self.pos += token.len;
+        }
+        token::InvalidInt(self.str_from(start).to_owned())
+    }
+
     fn cook_base_decimal(&self, start: usize) -> TokenKind {
         let slice = self.str_from(start);
-        token::Float(slice.parse().unwrap())
+        let value: f64 = slice.parse().unwrap();
+        if self.options.integral_exponents_as_int && !slice.contains('.') {
+            if let Some(i) = integral_f64_to_isize(value) {
+                return token::Int(i);
+            }
+        }
+        token::Float(value)
     }
 
     fn cook_base_quoted_string(&self, start: usize, terminated: bool) -> TokenKind {
         if !terminated {
-            return token::InvalidStr(StrError::Unterminated, self.pos);
+            return token::InvalidStr(StrError::Unterminated, self.pos - start);
         }
         let start = start + 1;
         let end = self.pos - 1;
         let slice = self.str_from_to(start, end);
-        match unescape::unescape_string(slice) {
+        // Fast path: without escapes or control chars there is nothing to
+        // unescape or reject, so the slice is the string.
+        if !slice.contains(|c: char| c == '\\' || c.is_control()) {
+            return token::Str(slice.to_owned());
+        }
+        match unescape::unescape_string(slice, &self.options.escapes) {
             Ok(s) => token::Str(s),
             Err((e, range)) => {
-                // plus 1 because we unescape after first '\"'
+                // `range` is in the source slice, which starts after the
+                // opening '\"', hence the plus 1. A terminated
+                // string never ends on an unpaired '\\' since `\"` is always
+                // skipped by the base lexer, so `LoneSlash` can't happen here.
                 token::InvalidStr(e.into(), range.start + 1)
             }
         }
@@ -123,17 +284,34 @@ impl<'a> Tokenizer<'a> {
         self.str_from_to(start, self.pos)
     }
 
+    /// Every offset comes from advancing the cursor by whole chars, so it is
+    /// always a char boundary.
     fn str_from_to(&self, start: usize, end: usize) -> &str {
+        debug_assert!(
+            self.input.is_char_boundary(start),
+            "{} splits a char",
+            start
+        );
+        debug_assert!(self.input.is_char_boundary(end), "{} splits a char", end);
         &self.input[start..end]
     }
 }
 
+/// Length of the `#` or `//` line starting `input`, including its line feed,
+/// or 0 if there is none.
+fn header_line_len(input: &str) -> usize {
+    if !input.starts_with('#') && !input.starts_with("//") {
+        return 0;
+    }
+    input.find('\n').map_or(input.len(), |lf| lf + 1)
+}
+
 impl<'a> Iterator for Tokenizer<'a> {
     type Item = (Token, bool);
 
     fn next(&mut self) -> Option<Self::Item> {
         let (token, whitespace) = self.next_token();
-        if matches!(token.kind, token::Eof) {
+        if token.kind.is_eof() {
             None
         } else {
             Some((token, whitespace))
@@ -142,9 +320,10 @@ impl<'a> Iterator for Tokenizer<'a> {
 }
 
 macro_rules! tokenize_test {
-    ($name:ident, $input:expr, $tokens:expr) => {
+    ($(#[$attr:meta])* $name:ident, $input:expr, $tokens:expr) => {
         #[cfg(test)]
         #[test]
+        $(#[$attr])*
         fn $name() {
             let mut token_iterator = tokenize($input);
 
@@ -201,6 +380,7 @@ tokenize_test!(
 );
 
 tokenize_test!(
+    #[allow(clippy::approx_constant)]
     it_tokenizes_a_decimal,
     "3.14",
     [(Token::new(token::Float(3.14), Span::new(0, 4)), false)]
@@ -276,6 +456,58 @@ tokenize_test!(
     ]
 );
 
+#[cfg(test)]
+#[test]
+fn it_tokenizes_integral_exponents_as_ints_when_enabled() {
+    let options = LexOptions {
+        integral_exponents_as_int: true,
+        ..LexOptions::default()
+    };
+    let kinds = |input| {
+        Tokenizer::with_options(input, options.clone())
+            .map(|(token, _)| token.kind)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(kinds("1e3"), [token::Int(1000)]);
+    assert_eq!(kinds("-2E+2"), [token::Int(-200)]);
+    assert_eq!(kinds("2.5e2"), [token::Float(250.0)]);
+    assert_eq!(kinds("1e-1"), [token::Float(0.1)]);
+    assert_eq!(kinds("1e30"), [token::Float(1e30)]);
+}
+
+#[cfg(test)]
+#[test]
+fn it_tokenizes_leading_zeros_as_invalid_ints_when_strict() {
+    let options = LexOptions {
+        strict_numbers: true,
+        ..LexOptions::default()
+    };
+    let tokens = |input| {
+        Tokenizer::with_options(input, options.clone())
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(
+        tokens("00"),
+        [Token::new(token::InvalidInt("00".into()), Span::new(0, 2))]
+    );
+    assert_eq!(
+        tokens("[-007.5e1, 0 0]"),
+        [
+            Token::new(token::OpenSquare, Span::new(0, 1)),
+            Token::new(token::InvalidInt("-007.5e1".into()), Span::new(1, 9)),
+            Token::new(token::Comma, Span::new(9, 10)),
+            Token::new(token::Int(0), Span::new(11, 12)),
+            Token::new(token::Int(0), Span::new(13, 14)),
+            Token::new(token::CloseSquare, Span::new(14, 15)),
+        ]
+    );
+    assert_eq!(
+        tokens("0.5"),
+        [Token::new(token::Float(0.5), Span::new(0, 3))]
+    );
+}
+
 // String literal tests.
 
 tokenize_test!(
@@ -332,8 +564,295 @@ tokenize_test!(
     )]
 );
 
+tokenize_test!(
+    it_tokenizes_a_string_unterminated_by_an_escaped_quote,
+    "\"abc\\\"",
+    [(
+        Token::new(
+            token::InvalidStr(StrError::Unterminated, 6),
+            Span::new(0, 6)
+        ),
+        false
+    )]
+);
+
+tokenize_test!(
+    it_tokenizes_a_string_ending_with_an_escaped_backslash,
+    "\"abc\\\\\"",
+    [(
+        Token::new(token::Str("abc\\".into()), Span::new(0, 7)),
+        false
+    )]
+);
+
+tokenize_test!(
+    it_tokenizes_a_string_with_a_lone_backslash,
+    "\"ab\\ c\"",
+    [(
+        Token::new(
+            token::InvalidStr(StrError::InvalidEscape, 3),
+            Span::new(0, 7)
+        ),
+        false
+    )]
+);
+
+tokenize_test!(
+    it_reports_bad_escapes_at_their_source_offset,
+    "\"\\u00e9\\n\\\"é\\q\"",
+    [(
+        Token::new(
+            token::InvalidStr(StrError::InvalidEscape, 13),
+            Span::new(0, 16)
+        ),
+        false
+    )]
+);
+
+#[cfg(test)]
+#[test]
+fn it_cooks_strings_with_and_without_escapes_alike() {
+    let cook = |input| match tokenize(input).next() {
+        Some((Token { kind, .. }, _)) => kind,
+        None => unreachable!(),
+    };
+    assert_eq!(cook("\"a é ♥\""), cook("\"a \\u00e9 \\u2665\""));
+    assert_eq!(cook("\"a é ♥\""), token::Str("a é ♥".into()));
+    assert_eq!(
+        cook("\"\t\""),
+        token::InvalidStr(StrError::BareHorizontalTab, 1)
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn it_reports_the_position_of_the_next_token() {
+    let input = "  [1,\n\t\"a\" ]  ";
+    let mut tokenizer = Tokenizer::new(input);
+    let mut positions = vec![];
+    loop {
+        let position = tokenizer.position();
+        let (token, _) = tokenizer.next_token();
+        if token.kind.is_eof() {
+            break;
+        }
+        assert_eq!(position, token.span.base);
+        positions.push(position);
+    }
+    assert_eq!(positions, [2, 3, 4, 7, 11]);
+    assert_eq!(tokenizer.position(), input.len());
+}
+
+#[cfg(test)]
+#[test]
+fn it_stops_whitespace_runs_at_the_limit() {
+    let options = LexOptions {
+        max_whitespace_run: Some(2),
+        ..Default::default()
+    };
+    let input = "[     1]";
+    let mut tokenizer = Tokenizer::with_options(input, options);
+    assert_eq!(tokenizer.next_token().0.kind, token::OpenSquare);
+    assert_eq!(tokenizer.position(), 1);
+    let (token, _) = tokenizer.next_token();
+    assert_eq!(
+        token,
+        Token::new(token::WhitespaceRunTooLong, Span::new(1, 4))
+    );
+    // The rest of the run is short enough to be skipped.
+    assert_eq!(tokenizer.position(), 6);
+    let (token, whitespace) = tokenizer.next_token();
+    assert_eq!(token, Token::new(token::Int(1), Span::new(6, 7)));
+    assert!(whitespace);
+}
+
+#[cfg(test)]
+#[test]
+fn it_splits_a_lone_minus_from_what_follows() {
+    let kinds: Vec<_> = spanned_tokens("-a").collect();
+    assert_eq!(
+        kinds,
+        [
+            (token::Unknown("-".into()), Span::new(0, 1)),
+            (token::InvalidIdent("a".into()), Span::new(1, 2)),
+        ]
+    );
+}
+
+#[cfg(test)]
+#[test]
+fn it_tokenizes_non_finite_numbers() {
+    let kinds = |input, options: &LexOptions| -> Vec<_> {
+        let mut tokenizer = Tokenizer::with_options(input, options.clone());
+        core::iter::from_fn(|| tokenizer.next().map(|(token, _)| token.kind)).collect()
+    };
+    let mut options = LexOptions {
+        non_finite_numbers: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        kinds("Infinity -Infinity +Infinity", &options),
+        [
+            token::Float(f64::INFINITY),
+            token::Float(f64::NEG_INFINITY),
+            token::Float(f64::INFINITY),
+        ]
+    );
+    assert!(matches!(kinds("+NaN", &options)[..], [token::Float(f)] if f.is_nan()));
+    assert_eq!(
+        kinds("infinity", &options),
+        [token::InvalidIdent("infinity".into())]
+    );
+
+    options.case_insensitive_literals = true;
+    assert_eq!(
+        kinds("infinity -INFINITY", &options),
+        [token::Float(f64::INFINITY), token::Float(f64::NEG_INFINITY)]
+    );
+
+    assert_eq!(
+        kinds("+Infinity", &LexOptions::default()),
+        [
+            token::Unknown("+".into()),
+            token::InvalidIdent("Infinity".into())
+        ]
+    );
+}
+
+// Spanned tests.
+
+#[cfg(test)]
+#[test]
+fn it_produces_char_boundary_spans() {
+    let input = "{\"clé\": [\"ça\", \"🇱🇺\"], \"ü\": ñ}";
+    let tokens: Vec<_> = spanned_tokens(input).collect();
+    for (_, span) in &tokens {
+        assert!(input.is_char_boundary(span.base));
+        assert!(input.is_char_boundary(span.base + span.len));
+    }
+    let slices: Vec<_> = tokens
+        .iter()
+        .map(|(_, span)| &input[span.base..span.base + span.len])
+        .collect();
+    assert_eq!(
+        slices,
+        [
+            "{",
+            "\"clé\"",
+            ":",
+            "[",
+            "\"ça\"",
+            ",",
+            "\"🇱🇺\"",
+            "]",
+            ",",
+            "\"ü\"",
+            ":",
+            "ñ",
+            "}"
+        ]
+    );
+    assert_eq!(tokens[1].0, token::Str("clé".into()));
+    assert_eq!(tokens[11].0, token::InvalidIdent("ñ".into()));
+}
+
+#[cfg(test)]
+#[test]
+fn it_returns_eof_from_next_token_at_the_end() {
+    let mut tokenizer = Tokenizer::new("[1, 2] ");
+    let mut kinds = vec![];
+    loop {
+        let (token, _) = tokenizer.next_token();
+        if token.kind.is_eof() {
+            assert_eq!(token.span, Span::new(7, 7));
+            break;
+        }
+        kinds.push(token.kind);
+    }
+    assert_eq!(
+        kinds,
+        [
+            token::OpenSquare,
+            token::Int(1),
+            token::Comma,
+            token::Int(2),
+            token::CloseSquare
+        ]
+    );
+    assert!(tokenizer.next_token().0.kind.is_eof());
+}
+
 // Full tests.
 
+#[cfg(test)]
+#[test]
+fn it_slices_span_text() {
+    let input = "[\"clé\", \"ü\"]";
+    assert_eq!(span_text(input, &Span::new(1, 7)), "\"clé\"");
+    assert_eq!(&input[Span::new(1, 7).to_range()], "\"clé\"");
+    // Byte 5 is the second byte of `é`.
+    assert_eq!(span_text(input, &Span::new(4, 5)), "é");
+    assert_eq!(span_text(input, &Span::new(5, 7)), "é\"");
+    assert_eq!(span_text(input, &Span::new(10, 100)), "ü\"]");
+    assert_eq!(span_text(input, &Span::new(100, 100)), "");
+}
+
+/// Strings of `len` chars drawn from JSON punctuation, keyword fragments and
+/// multibyte chars, from a fixed seed so failures are reproducible.
+#[cfg(test)]
+fn random_inputs(count: usize, len: usize) -> Vec<String> {
+    const ALPHABET: &[&str] = &[
+        "{", "}", "[", "]", ":", ",", "#", "/", "\"", "\\", "\\u", "\\x", " ", "\n", "-", "+", "0",
+        "7", ".", "e", "E", "true", "nul", "Infinity", "NaN", "é", "ñ", "€", "😀", "🇱🇺", "\u{0}",
+        "\u{200B}", "\u{FEFF}",
+    ];
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        // xorshift64
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state as usize
+    };
+    (0..count)
+        .map(|_| {
+            let n = next() % (len + 1);
+            (0..n).map(|_| ALPHABET[next() % ALPHABET.len()]).collect()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[test]
+fn it_never_slices_mid_char() {
+    let lenient = LexOptions {
+        escapes: EscapeOptions {
+            hex_escapes: true,
+            line_continuations: true,
+        },
+        integral_exponents_as_int: true,
+        strict_numbers: true,
+        non_finite_numbers: true,
+        case_insensitive_literals: true,
+        skip_header_line: true,
+        max_whitespace_run: Some(1),
+    };
+    for input in random_inputs(2000, 24) {
+        for options in [LexOptions::default(), lenient.clone()] {
+            for (token, _) in Tokenizer::with_options(&input, options.clone()) {
+                assert!(input.get(token.span.to_range()).is_some(), "{:?}", input);
+            }
+            let parse_options = crate::ParseOptions {
+                lexer: options,
+                ..Default::default()
+            };
+            if let Err(err) = crate::parse_with(&input, &parse_options) {
+                assert!(input.get(err.span.to_range()).is_some(), "{:?}", input);
+            }
+        }
+    }
+}
+
 tokenize_test!(
     it_tokenizes_an_array_of_objects,
     "[{ \"name\": \"Adrien\", \"age\": 23, \"hungry\": true, \"health\": 0.9, \"girlfriend\": null }]",
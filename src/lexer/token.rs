@@ -1,3 +1,6 @@
+use alloc::{borrow::ToOwned, string::String};
+use core::ops::Range;
+
 pub use TokenKind::*;
 
 use super::unescape::EscapeError;
@@ -35,13 +38,29 @@ pub enum TokenKind {
     /// Parser will never see Whitespace tokens
     Whitespace,
     /// Not part of spec
+    ///
+    /// The offset is where the error is in the source text, in bytes from
+    /// the opening quote, e.g. the `\\` of a bad escape. Escapes before it
+    /// are counted by their source length, not that of what they decode to.
     InvalidStr(StrError, usize),
     InvalidIdent(String),
+    /// Number with leading zeros, only produced with `LexOptions::strict_numbers`.
+    InvalidInt(String),
+    /// Run of whitespace longer than `LexOptions::max_whitespace_run`.
+    WhitespaceRunTooLong,
     Unknown(String),
     Eof,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+impl TokenKind {
+    /// Returns true for the `Eof` token that `Tokenizer::next_token` returns
+    /// once the input is exhausted.
+    pub fn is_eof(&self) -> bool {
+        matches!(self, Eof)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Span {
     pub base: usize,
     pub len: usize,
@@ -52,12 +71,37 @@ impl Span {
         let (base, len) = (lo, hi - lo);
         Span { base, len }
     }
+
+    /// The byte range covered by this span.
+    pub fn to_range(&self) -> Range<usize> {
+        self.base..self.base + self.len
+    }
+}
+
+/// The text of `input` covered by `span`.
+///
+/// Never panics: the span is clamped to the input, and an end that falls
+/// inside a multibyte char is moved past it, a start back before it.
+pub fn span_text<'a>(input: &'a str, span: &Span) -> &'a str {
+    let mut start = span.base.min(input.len());
+    let mut end = span.base.saturating_add(span.len).min(input.len());
+    while !input.is_char_boundary(start) {
+        start -= 1;
+    }
+    while !input.is_char_boundary(end) {
+        end += 1;
+    }
+    &input[start..end]
 }
 
 const DUMMY_SPAN: Span = Span { base: 0, len: 0 };
 
 /// Errors that can occur during string parsing.
+///
+/// New variants may be added in minor releases, so matches outside this crate
+/// need a catch-all arm.
 #[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum StrError {
     Unterminated,
     InvalidEscape,
@@ -69,6 +113,7 @@ pub enum StrError {
     BadControlChar,
     EscapeOnlyChar,
     BadUnicodeEscape,
+    BadHexEscape,
     LoneSurrogateUnicodeEscape,
     OutOfRangeUnicodeEscape,
 }
@@ -86,6 +131,7 @@ impl From<EscapeError> for StrError {
             EscapeError::BadControlChar => StrError::BadControlChar,
             EscapeError::EscapeOnlyChar => StrError::EscapeOnlyChar,
             EscapeError::BadUnicodeEscape => StrError::BadUnicodeEscape,
+            EscapeError::BadHexEscape => StrError::BadHexEscape,
             EscapeError::LoneSurrogateUnicodeEscape => StrError::LoneSurrogateUnicodeEscape,
             EscapeError::OutOfRangeUnicodeEscape => StrError::OutOfRangeUnicodeEscape,
         }
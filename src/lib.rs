@@ -1,13 +1,58 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
 pub mod ast;
+pub mod decode;
+#[cfg(feature = "std")]
+pub mod fs;
+pub mod intern;
 pub mod lexer;
 pub mod parser;
+pub mod path;
+pub mod pointer;
+pub mod schema;
+pub mod ser;
+pub mod spanned;
+pub mod stream;
+#[cfg(feature = "toml")]
+pub mod toml;
+#[cfg(feature = "yaml")]
+pub mod yaml;
 
-pub use parser::parse;
+#[cfg(feature = "std")]
+pub use fs::{parse_reader, read_file, Error};
+pub use lexer::{span_text, Span, StrError};
+#[cfg(feature = "std")]
+pub use parser::parse_with_raw;
+pub use parser::{
+    parse, parse_bytes_lossy, parse_with, parse_with_handler, parse_with_warnings, Handler,
+    NodeBuilder, ParseError, ParseErrorKind, ParseOptions, ParseResult, ParseWarning,
+    ParseWarningKind, StrictnessProfile,
+};
+pub use ser::{analyze_whitespace, minify, minify_savings, prettify};
 
 #[cfg(test)]
 mod tests {
     use super::ast::Node;
     use super::parser::parse;
+    use super::{ParseError, ParseErrorKind, ParseResult, Span};
+
+    fn parse_first_element(input: &str) -> ParseResult {
+        match parse(input)? {
+            Node::Array(mut elements) if !elements.is_empty() => Ok(elements.remove(0)),
+            _ => Err(ParseError {
+                kind: ParseErrorKind::UnexpectedEof,
+                span: Span::new(0, 0),
+            }),
+        }
+    }
+
+    #[test]
+    fn it_exposes_the_parse_result_alias() {
+        assert_eq!(parse_first_element("[true]"), Ok(Node::True));
+        let _: ParseResult<()> = parse("null").map(|_| ());
+    }
 
     #[test]
     fn it_parses() {
@@ -16,4 +61,16 @@ mod tests {
         let expected = Node::Array(vec![Node::Object(vec![])]);
         assert_eq!(node, Ok(expected));
     }
+
+    #[test]
+    fn it_parses_an_empty_array() {
+        assert_eq!(parse("[]"), Ok(Node::Array(vec![])));
+        assert_eq!(parse("[ ]"), Ok(Node::Array(vec![])));
+    }
+
+    #[test]
+    fn it_parses_an_empty_object() {
+        assert_eq!(parse("{}"), Ok(Node::Object(vec![])));
+        assert_eq!(parse("{ }"), Ok(Node::Object(vec![])));
+    }
 }
@@ -1,21 +1,65 @@
-use std::iter::Peekable;
+use alloc::{borrow::ToOwned, format, string::String, vec, vec::Vec};
+use core::iter::Peekable;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
 
 use crate::ast::Node;
+#[cfg(feature = "std")]
+use crate::lexer::spanned_tokens;
 use crate::lexer::{
-    token,
+    span_text, token,
     token::{Span, StrError},
-    Token, TokenKind, Tokenizer,
+    LexOptions, Token, TokenKind, Tokenizer,
 };
 
 use self::ParseErrorKind::*;
 
+/// New variants may be added in minor releases, so matches outside this crate
+/// need a catch-all arm.
 #[derive(Debug, PartialEq)]
+#[non_exhaustive]
 pub enum ParseErrorKind {
     UnexpectedContinuation(TokenKind),
     UnexpectedEof,
     UnexpectedToken(TokenKind),
+    /// A token other than `:` followed an object key.
+    ExpectedColon(TokenKind),
+    /// A token other than `,` or the closing delimiter followed a member or
+    /// element.
+    ExpectedComma(TokenKind),
+    /// A token that can't start a value where one was expected, e.g. the `}`
+    /// of `{"a":}`, or `Eof` if the input ended instead.
+    ExpectedValue(TokenKind),
+    /// The input is empty or only whitespace.
+    EmptyInput,
+    /// A document that isn't an object or array, with
+    /// `ParseOptions::require_container`.
+    ExpectedContainer(TokenKind),
+    /// A `,` where a value or object key was expected, e.g. `[1,,2]`.
+    UnexpectedComma,
+    /// An object key that isn't a string, e.g. `{1: 2}` or `{a: 2}`.
+    NonStringKey(TokenKind),
+    /// A container closed by the wrong delimiter, e.g. `[1}`.
+    MismatchedDelimiter {
+        opened: TokenKind,
+        found: TokenKind,
+    },
+    /// The input has more tokens than `ParseOptions::max_tokens` allows.
+    TooManyTokens,
+    /// An object has more members than `ParseOptions::max_object_members`
+    /// allows.
+    TooManyMembers,
+    /// An array has more elements than `ParseOptions::max_array_elements`
+    /// allows.
+    TooManyElements,
+    /// A run of whitespace is longer than `LexOptions::max_whitespace_run`
+    /// allows.
+    WhitespaceRunTooLong,
     InvalidStr(StrError),
     InvalidIdent(String),
+    /// A number with leading zeros, e.g. `007`, with
+    /// `LexOptions::strict_numbers`.
+    InvalidInt(String),
     UnknownToken(String),
 }
 
@@ -26,7 +70,7 @@ pub struct ParseError {
 }
 
 impl ParseError {
-    fn new(kind: ParseErrorKind, span: Span) -> Self {
+    pub(crate) fn new(kind: ParseErrorKind, span: Span) -> Self {
         Self { kind, span }
     }
 
@@ -42,129 +86,456 @@ impl ParseError {
         Self::new(UnexpectedContinuation(token.kind), token.span)
     }
 
-    fn from_token(token: Token) -> Self {
+    fn from_token(token: Token, input: &str) -> Self {
         match token.kind {
             token::InvalidStr(err, offset) => {
-                let loc = token.span.base + offset;
+                debug_assert!(offset <= token.span.len, "offset past the string token");
+                // Clamp so the span can always be used to slice the input.
+                let loc = token
+                    .span
+                    .base
+                    .checked_add(offset)
+                    .map_or(input.len(), |loc| loc.min(input.len()));
+                debug_assert!(input.is_char_boundary(loc));
                 let span = Span::new(loc, loc);
                 Self::new(InvalidStr(err), span)
             }
             token::InvalidIdent(ident) => Self::new(InvalidIdent(ident), token.span),
+            token::InvalidInt(int) => Self::new(InvalidInt(int), token.span),
             token::Unknown(unk) => Self::new(UnknownToken(unk), token.span),
             token::Eof => Self::new(UnexpectedEof, token.span),
+            token::Comma => Self::new(UnexpectedComma, token.span),
+            token::WhitespaceRunTooLong => Self::new(WhitespaceRunTooLong, token.span),
             _ => Self::new(UnexpectedToken(token.kind), token.span),
         }
     }
+
+    /// The text of `input`, the input that failed to parse, this error
+    /// points at. Empty for errors at a position, such as `UnexpectedEof`.
+    pub fn source_slice<'a>(&self, input: &'a str) -> &'a str {
+        span_text(input, &self.span)
+    }
+
+    /// A short suggestion on how to fix the error, if there is one.
+    pub fn hint(&self) -> Option<String> {
+        let hint = match &self.kind {
+            MismatchedDelimiter { opened, .. } => {
+                let (open, close) = match opened {
+                    token::OpenSquare => ("[", "]"),
+                    _ => ("{", "}"),
+                };
+                return Some(format!("`{}` must be closed by `{}`", open, close));
+            }
+            InvalidIdent(ident) => {
+                let keyword = misspelled_keyword(ident)?;
+                return Some(format!("did you mean `{}`?", keyword));
+            }
+            ExpectedColon(_) => "expected `:` after object key",
+            ExpectedComma(_) => "expected `,` between members or elements",
+            ExpectedValue(_) => {
+                "expected a value: an object, array, string, number, `true`, `false` or `null`"
+            }
+            EmptyInput => "the input is empty, a JSON document needs at least one value",
+            ExpectedContainer(_) => "the document must be an object or an array",
+            UnexpectedComma => "JSON does not allow empty elements, remove the extra `,`",
+            NonStringKey(_) => "object keys must be double-quoted strings",
+            InvalidInt(_) => "numbers can't have leading zeros",
+            _ => return None,
+        };
+        Some(hint.to_owned())
+    }
+}
+
+/// The keyword `ident` most likely is a misspelling of, if any: the same
+/// letters in another case, or with a single char inserted, removed or
+/// substituted.
+fn misspelled_keyword(ident: &str) -> Option<&'static str> {
+    let ident = ident.to_ascii_lowercase();
+    ["true", "false", "null"]
+        .into_iter()
+        .find(|keyword| within_one_edit(&ident, keyword))
+}
+
+fn within_one_edit(a: &str, b: &str) -> bool {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if long.len() - short.len() > 1 {
+        return false;
+    }
+    let prefix = short.iter().zip(&long).take_while(|(x, y)| x == y).count();
+    if short.len() == long.len() {
+        prefix == short.len() || short[prefix + 1..] == long[prefix + 1..]
+    } else {
+        short[prefix..] == long[prefix + 1..]
+    }
+}
+
+/// Non-standard syntax that lenient options let through, reported by
+/// `parse_with_warnings`.
+#[derive(Debug, PartialEq)]
+#[non_exhaustive]
+pub enum ParseWarningKind {
+    /// A `=` separated an object key from its value, instead of a `:`.
+    EqualsSeparator,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ParseWarning {
+    pub kind: ParseWarningKind,
+    pub span: Span,
+}
+
+/// Result of parsing, defaulting to a parsed `Node`.
+pub type ParseResult<T = Node> = Result<T, ParseError>;
+
+/// Options controlling how strictly the parser follows the JSON spec.
+///
+/// The default options are strict.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Stop after the first complete value and ignore whatever follows it,
+    /// instead of erroring with `UnexpectedContinuation`.
+    pub allow_trailing_content: bool,
+    /// Maximum number of tokens to consume before giving up with
+    /// `TooManyTokens`, bounding the work done on adversarial inputs.
+    /// Unlimited when `None`.
+    pub max_tokens: Option<usize>,
+    /// Maximum number of members in any single object before giving up with
+    /// `TooManyMembers`. Unlimited when `None`.
+    pub max_object_members: Option<usize>,
+    /// Maximum number of elements in any single array before giving up with
+    /// `TooManyElements`. Unlimited when `None`.
+    pub max_array_elements: Option<usize>,
+    /// Accept `=` in place of `:` between an object key and its value, e.g.
+    /// `{"a"=1}`, with an `EqualsSeparator` warning.
+    pub allow_equals_separator: bool,
+    /// Parse empty or whitespace-only input as `null` rather than erroring
+    /// with `EmptyInput`.
+    pub allow_empty: bool,
+    /// Only accept an object or an array as the document, erroring with
+    /// `ExpectedContainer` otherwise.
+    pub require_container: bool,
+    /// Non-standard syntax to accept in the lexer.
+    pub lexer: LexOptions,
+}
+
+/// Presets of the options deciding what counts as a whole document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrictnessProfile {
+    /// Exactly one value of any type, as in RFC 8259, surrounded by nothing
+    /// but whitespace. Empty input is an `EmptyInput` error. These are the
+    /// default options.
+    Rfc8259,
+    /// The first value of any type, ignoring whatever follows it. Empty
+    /// input parses as `null`.
+    Lenient,
+    /// An object or array, which ends at its closing delimiter so it can be
+    /// followed by other text, which is ignored. Any other value is an
+    /// `ExpectedContainer` error and empty input an `EmptyInput` one.
+    Embeddable,
+}
+
+impl StrictnessProfile {
+    /// The default options with this profile's document handling.
+    pub fn options(self) -> ParseOptions {
+        let default = ParseOptions::default();
+        match self {
+            StrictnessProfile::Rfc8259 => default,
+            StrictnessProfile::Lenient => ParseOptions {
+                allow_trailing_content: true,
+                allow_empty: true,
+                ..default
+            },
+            StrictnessProfile::Embeddable => ParseOptions {
+                allow_trailing_content: true,
+                require_container: true,
+                ..default
+            },
+        }
+    }
+}
+
+pub fn parse(input: &str) -> ParseResult {
+    parse_with(input, &ParseOptions::default())
 }
 
-pub fn parse(input: &str) -> Result<Node, ParseError> {
-    let mut parser = Parser::new(input);
-    let res = parser.parse();
-    res
+pub fn parse_with(input: &str, options: &ParseOptions) -> ParseResult {
+    parse_with_handler(input, options, &mut NodeBuilder)
 }
 
-pub struct Parser<'a> {
+/// Receives the structure of a document as the parser recognises it, and
+/// decides what it is built into.
+///
+/// Values are built bottom-up: the parser hands over every key and scalar as
+/// it is read, and every container once all of its contents have been
+/// handled. A handler can, for instance, intern repeated keys or collect
+/// metrics without materialising a `Node` tree.
+pub trait Handler {
+    type Key;
+    type Value;
+
+    fn key(&mut self, key: String) -> Self::Key;
+    /// Called for strings, numbers, booleans and null.
+    fn scalar(&mut self, node: Node) -> Self::Value;
+    fn object(&mut self, members: Vec<(Self::Key, Self::Value)>) -> Self::Value;
+    fn array(&mut self, elements: Vec<Self::Value>) -> Self::Value;
+}
+
+/// An object member as built by handler `H`.
+type Member<H> = (<H as Handler>::Key, <H as Handler>::Value);
+
+/// The `Handler` used by `parse`, building a `Node`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NodeBuilder;
+
+impl Handler for NodeBuilder {
+    type Key = String;
+    type Value = Node;
+
+    fn key(&mut self, key: String) -> String {
+        key
+    }
+
+    fn scalar(&mut self, node: Node) -> Node {
+        node
+    }
+
+    fn object(&mut self, members: Vec<(String, Node)>) -> Node {
+        Node::Object(members)
+    }
+
+    fn array(&mut self, elements: Vec<Node>) -> Node {
+        Node::Array(elements)
+    }
+}
+
+/// Like `parse_with`, driving `handler` with the structure of `input`
+/// instead of building a `Node`.
+pub fn parse_with_handler<H: Handler>(
+    input: &str,
+    options: &ParseOptions,
+    handler: &mut H,
+) -> ParseResult<H::Value> {
+    let mut parser = Parser::new(input, options.clone(), handler);
+    parser.parse()
+}
+
+/// Like `parse_with`, also returning where lenient `options` accepted
+/// non-standard syntax.
+pub fn parse_with_warnings(
+    input: &str,
+    options: &ParseOptions,
+) -> ParseResult<(Node, Vec<ParseWarning>)> {
+    let mut builder = NodeBuilder;
+    let mut parser = Parser::new(input, options.clone(), &mut builder);
+    let node = parser.parse()?;
+    Ok((node, parser.warnings))
+}
+
+/// Parses possibly invalid UTF-8, replacing every invalid sequence with
+/// U+FFFD first instead of failing.
+///
+/// This changes the contents of strings with invalid bytes, and error spans
+/// are offsets in the decoded text, which may no longer match `input`.
+pub fn parse_bytes_lossy(input: &[u8]) -> ParseResult {
+    parse(&String::from_utf8_lossy(input))
+}
+
+/// Parses `input` like `parse`, also returning the source text of every
+/// number keyed by its span.
+///
+/// `Int`s and `Float`s can't represent every literal exactly, e.g. `0.1` or
+/// digits beyond an `f64`'s precision. The spans match those of
+/// `parse_spanned`, so the exact text of any number can be looked up.
+#[cfg(feature = "std")]
+pub fn parse_with_raw(input: &str) -> ParseResult<(Node, HashMap<Span, String>)> {
+    let node = parse(input)?;
+    let raw = spanned_tokens(input)
+        .filter(|(kind, _)| matches!(kind, token::Int(_) | token::Float(_)))
+        .map(|(_, span)| {
+            let text = input[span.base..span.base + span.len].to_owned();
+            (span, text)
+        })
+        .collect();
+    Ok((node, raw))
+}
+
+pub struct Parser<'a, 'h, H: Handler> {
     input: &'a str,
     tokenizer: Peekable<Tokenizer<'a>>,
+    options: ParseOptions,
+    /// Number of tokens consumed so far.
+    consumed: usize,
+    warnings: Vec<ParseWarning>,
+    handler: &'h mut H,
 }
 
-impl<'a> Parser<'a> {
+impl<'a, 'h, H: Handler> Parser<'a, 'h, H> {
     /// Create a new parser.
-    fn new(input: &'a str) -> Self {
-        let tokenizer = Tokenizer::new(input).peekable();
-        Self { input, tokenizer }
+    fn new(input: &'a str, options: ParseOptions, handler: &'h mut H) -> Self {
+        let tokenizer = Tokenizer::with_options(input, options.lexer.clone()).peekable();
+        Self {
+            input,
+            tokenizer,
+            options,
+            consumed: 0,
+            warnings: vec![],
+            handler,
+        }
     }
 
-    fn parse(&mut self) -> Result<Node, ParseError> {
-        let node = self.json()?;
-        self.end()?;
-        Ok(node)
+    fn parse(&mut self) -> Result<H::Value, ParseError> {
+        match self.tokenizer.peek() {
+            None if self.options.allow_empty => return Ok(self.handler.scalar(Node::Null)),
+            None => return Err(ParseError::new(EmptyInput, Span::new(0, self.input.len()))),
+            Some((token, _)) if self.options.require_container => match token.kind {
+                token::OpenBracket | token::OpenSquare => (),
+                _ => {
+                    let kind = ExpectedContainer(token.kind.clone());
+                    return Err(ParseError::new(kind, token.span.clone()));
+                }
+            },
+            _ => (),
+        }
+        let value = self.json()?;
+        if !self.options.allow_trailing_content {
+            self.end()?;
+        }
+        Ok(value)
     }
 
-    fn json(&mut self) -> Result<Node, ParseError> {
+    fn json(&mut self) -> Result<H::Value, ParseError> {
         self.value()
     }
 
-    fn value(&mut self) -> Result<Node, ParseError> {
-        let token = self.peek()?;
-        let node = match token.kind {
-            token::OpenBracket => self.object()?,
-            token::OpenSquare => self.array()?,
+    fn value(&mut self) -> Result<H::Value, ParseError> {
+        let token = match self.tokenizer.peek() {
+            Some((token, _)) => token,
+            None => {
+                let eof = ParseError::unexpected_eof(self.input).span;
+                return Err(ParseError::new(ExpectedValue(token::Eof), eof));
+            }
+        };
+        let scalar = match token.kind {
+            token::OpenBracket => return self.object(),
+            token::OpenSquare => return self.array(),
             token::Str(_) => self.string()?,
             token::Int(_) => self.integer()?,
             token::Float(_) => self.float()?,
             token::True => self.ident_true()?,
             token::False => self.ident_false()?,
             token::Null => self.ident_null()?,
-            _ => return Err(ParseError::from_token(token.clone())),
+            token::CloseBracket | token::CloseSquare | token::Colon => {
+                return Err(ParseError::new(
+                    ExpectedValue(token.kind.clone()),
+                    token.span.clone(),
+                ))
+            }
+            _ => return Err(ParseError::from_token(token.clone(), self.input)),
         };
-        Ok(node)
+        Ok(self.handler.scalar(scalar))
     }
 
-    fn object(&mut self) -> Result<Node, ParseError> {
-        self.eat_open_bracket()?;
+    fn object(&mut self) -> Result<H::Value, ParseError> {
+        let open = self.eat_open_bracket()?;
         let token = self.peek()?;
-        let items: Vec<(String, Node)> = match token.kind {
+        let items = match token.kind {
             token::CloseBracket => vec![],
-            _ => self.members()?,
+            token::CloseSquare => return Err(self.mismatched_delimiter(token::OpenBracket)),
+            _ => self.members(open)?,
         };
         self.eat_close_bracket()?;
-        Ok(Node::Object(items))
+        Ok(self.handler.object(items))
     }
 
-    fn members(&mut self) -> Result<Vec<(String, Node)>, ParseError> {
-        let mut members = vec![self.member()?];
+    /// Parses the members of the object opened at `open`.
+    fn members(&mut self, open: Span) -> Result<Vec<Member<H>>, ParseError> {
+        let mut members = vec![];
+        // The delimiter before the next member, which the cap error ends at.
+        let mut separator = open.clone();
         loop {
+            if let Some(max) = self.options.max_object_members {
+                if members.len() >= max {
+                    let span = Span::new(open.base, separator.base + separator.len);
+                    return Err(ParseError::new(TooManyMembers, span));
+                }
+            }
+            members.push(self.member()?);
             let token = self.peek()?;
             match token.kind {
                 token::CloseBracket => {
                     break;
                 }
-                _ => {
-                    self.eat_comma()?;
-                    let next_member = self.member()?;
-                    members.push(next_member);
-                }
+                token::CloseSquare => return Err(self.mismatched_delimiter(token::OpenBracket)),
+                _ => separator = self.eat_comma()?,
             };
         }
         Ok(members)
     }
 
-    fn member(&mut self) -> Result<(String, Node), ParseError> {
+    fn member(&mut self) -> Result<Member<H>, ParseError> {
         let token = self.next()?;
         let key = match token.kind {
             token::Str(s) => s,
-            _ => return Err(ParseError::from_token(token)),
+            kind @ (token::Int(_)
+            | token::Float(_)
+            | token::True
+            | token::False
+            | token::Null
+            | token::InvalidIdent(_)) => {
+                return Err(ParseError::new(NonStringKey(kind), token.span))
+            }
+            token::CloseSquare => {
+                let kind = MismatchedDelimiter {
+                    opened: token::OpenBracket,
+                    found: token::CloseSquare,
+                };
+                return Err(ParseError::new(kind, token.span));
+            }
+            // Broken strings, stray commas and other structural tokens have
+            // better errors.
+            _ => return Err(ParseError::from_token(token, self.input)),
         };
+        let key = self.handler.key(key);
         self.eat_colon()?;
         let value = self.value()?;
         Ok((key, value))
     }
 
-    fn array(&mut self) -> Result<Node, ParseError> {
-        self.eat_open_square()?;
-        let token = self.peek()?;
-        let items: Vec<Node> = match token.kind {
-            token::CloseSquare => vec![],
-            _ => self.elements()?,
+    fn array(&mut self) -> Result<H::Value, ParseError> {
+        let open = self.eat_open_square()?;
+        // At the end of the input, let `elements` report the missing value.
+        let kind = self.tokenizer.peek().map(|(token, _)| &token.kind);
+        let items = match kind {
+            Some(token::CloseSquare) => vec![],
+            Some(token::CloseBracket) => return Err(self.mismatched_delimiter(token::OpenSquare)),
+            _ => self.elements(open)?,
         };
         self.eat_close_square()?;
-        Ok(Node::Array(items))
+        Ok(self.handler.array(items))
     }
 
-    fn elements(&mut self) -> Result<Vec<Node>, ParseError> {
-        let mut elements = vec![self.value()?];
+    /// Parses the elements of the array opened at `open`.
+    fn elements(&mut self, open: Span) -> Result<Vec<H::Value>, ParseError> {
+        let mut elements = vec![];
+        // The delimiter before the next element, which the cap error ends at.
+        let mut separator = open.clone();
         loop {
+            if let Some(max) = self.options.max_array_elements {
+                if elements.len() >= max {
+                    let span = Span::new(open.base, separator.base + separator.len);
+                    return Err(ParseError::new(TooManyElements, span));
+                }
+            }
+            elements.push(self.value()?);
             let token = self.peek()?;
             match token.kind {
                 token::CloseSquare => {
                     break;
                 }
-                _ => {
-                    self.eat_comma()?;
-                    let next_element = self.value()?;
-                    elements.push(next_element);
-                }
+                token::CloseBracket => return Err(self.mismatched_delimiter(token::OpenSquare)),
+                _ => separator = self.eat_comma()?,
             };
         }
         Ok(elements)
@@ -174,7 +545,7 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::Str(s) => Ok(Node::Str(s)),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -182,7 +553,7 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::Int(i) => Ok(Node::Int(i)),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -190,7 +561,7 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::Float(i) => Ok(Node::Float(i)),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -198,7 +569,7 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::True => Ok(Node::True),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -206,7 +577,7 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::False => Ok(Node::False),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -214,15 +585,15 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::Null => Ok(Node::Null),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
-    fn eat_open_bracket(&mut self) -> Result<(), ParseError> {
+    fn eat_open_bracket(&mut self) -> Result<Span, ParseError> {
         let token = self.next()?;
         match token.kind {
-            token::OpenBracket => Ok(()),
-            _ => Err(ParseError::from_token(token)),
+            token::OpenBracket => Ok(token.span),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -230,15 +601,15 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::CloseBracket => Ok(()),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
-    fn eat_open_square(&mut self) -> Result<(), ParseError> {
+    fn eat_open_square(&mut self) -> Result<Span, ParseError> {
         let token = self.next()?;
         match token.kind {
-            token::OpenSquare => Ok(()),
-            _ => Err(ParseError::from_token(token)),
+            token::OpenSquare => Ok(token.span),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -246,7 +617,7 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::CloseSquare => Ok(()),
-            _ => Err(ParseError::from_token(token)),
+            _ => Err(ParseError::from_token(token, self.input)),
         }
     }
 
@@ -254,38 +625,597 @@ impl<'a> Parser<'a> {
         let token = self.next()?;
         match token.kind {
             token::Colon => Ok(()),
-            _ => Err(ParseError::from_token(token)),
+            token::Unknown(ref unk) if unk == "=" && self.options.allow_equals_separator => {
+                self.warnings.push(ParseWarning {
+                    kind: ParseWarningKind::EqualsSeparator,
+                    span: token.span,
+                });
+                Ok(())
+            }
+            _ => Err(ParseError::new(ExpectedColon(token.kind), token.span)),
         }
     }
 
-    fn eat_comma(&mut self) -> Result<(), ParseError> {
+    fn eat_comma(&mut self) -> Result<Span, ParseError> {
         let token = self.next()?;
         match token.kind {
-            token::Comma => Ok(()),
-            _ => Err(ParseError::from_token(token)),
+            token::Comma => Ok(token.span),
+            _ => Err(ParseError::new(ExpectedComma(token.kind), token.span)),
+        }
+    }
+
+    /// Consumes the wrong closing delimiter of a container opened by `opened`.
+    fn mismatched_delimiter(&mut self, opened: TokenKind) -> ParseError {
+        match self.next() {
+            Ok(token) => ParseError::new(
+                MismatchedDelimiter {
+                    opened,
+                    found: token.kind,
+                },
+                token.span,
+            ),
+            Err(err) => err,
         }
     }
 
     /// Peek at the next token.
     fn peek(&mut self) -> Result<&Token, ParseError> {
         match self.tokenizer.peek() {
+            Some((token, _)) if token.kind == token::WhitespaceRunTooLong => {
+                Err(ParseError::new(WhitespaceRunTooLong, token.span.clone()))
+            }
             Some((token, _)) => Ok(token),
-            None => Err(ParseError::unexpected_eof(&self.input)),
+            None => Err(ParseError::unexpected_eof(self.input)),
         }
     }
 
     /// Get the next token, moving the index along one.
     fn next(&mut self) -> Result<Token, ParseError> {
         match self.tokenizer.next() {
-            Some((token, _)) => Ok(token),
-            None => Err(ParseError::unexpected_eof(&self.input)),
+            Some((token, _)) => {
+                self.consumed += 1;
+                match self.options.max_tokens {
+                    Some(max) if self.consumed > max => {
+                        Err(ParseError::new(TooManyTokens, token.span))
+                    }
+                    _ if token.kind == token::WhitespaceRunTooLong => {
+                        Err(ParseError::new(WhitespaceRunTooLong, token.span))
+                    }
+                    _ => Ok(token),
+                }
+            }
+            None => Err(ParseError::unexpected_eof(self.input)),
         }
     }
 
     fn end(&mut self) -> Result<(), ParseError> {
         match self.tokenizer.next() {
             None => Ok(()),
+            Some((token, _)) if token.kind == token::WhitespaceRunTooLong => {
+                Err(ParseError::from_token(token, self.input))
+            }
             Some((token, _)) => Err(ParseError::unexpected_continuation(token)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::EscapeOptions;
+
+    #[test]
+    fn it_rejects_trailing_content_by_default() {
+        let err = parse("[1] //comment").unwrap_err();
+        assert_eq!(err.kind, UnexpectedContinuation(token::Unknown("/".into())));
+        assert_eq!(err.span, Span::new(4, 5));
+
+        let err = parse("1 2").unwrap_err();
+        assert_eq!(err.kind, UnexpectedContinuation(token::Int(2)));
+        assert_eq!(err.span, Span::new(2, 3));
+    }
+
+    #[test]
+    fn it_accepts_trailing_whitespace_by_default() {
+        assert_eq!(parse("[1] \n"), Ok(Node::Array(vec![Node::Int(1)])));
+    }
+
+    #[test]
+    fn it_ignores_trailing_content_when_allowed() {
+        let options = ParseOptions {
+            allow_trailing_content: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            parse_with("[1] //comment", &options),
+            Ok(Node::Array(vec![Node::Int(1)]))
+        );
+        assert_eq!(parse_with("1 2", &options), Ok(Node::Int(1)));
+    }
+
+    #[test]
+    fn it_reports_a_missing_colon() {
+        let err = parse("{\"a\" 1}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedColon(token::Int(1)), Span::new(5, 6))
+        );
+    }
+
+    #[test]
+    fn it_reports_a_missing_comma() {
+        let err = parse("{\"a\":1 \"b\":2}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedComma(token::Str("b".into())), Span::new(7, 10))
+        );
+
+        let err = parse("[1 2]").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedComma(token::Int(2)), Span::new(3, 4))
+        );
+    }
+
+    #[test]
+    fn it_parses_hex_escapes_when_enabled() {
+        let mut options = ParseOptions::default();
+        options.lexer.escapes = EscapeOptions {
+            hex_escapes: true,
+            ..EscapeOptions::default()
+        };
+        assert_eq!(parse_with("\"\\x41\"", &options), Ok(Node::Str("A".into())));
+
+        let err = parse("\"\\x41\"").unwrap_err();
+        assert_eq!(err.kind, InvalidStr(StrError::InvalidEscape));
+    }
+
+    #[test]
+    fn it_matches_error_kinds_with_a_catch_all() {
+        let describe = |input| match parse(input).unwrap_err().kind {
+            UnexpectedEof => "eof",
+            InvalidStr(_) => "string",
+            _ => "other",
+        };
+        assert_eq!(describe("{"), "eof");
+        assert_eq!(describe("\"\\q\""), "string");
+        assert_eq!(describe("{1}"), "other");
+    }
+
+    #[test]
+    fn it_reports_a_doubled_comma() {
+        let err = parse("[1,,2]").unwrap_err();
+        assert_eq!(err, ParseError::new(UnexpectedComma, Span::new(3, 4)));
+        assert!(err.hint().unwrap().contains("extra `,`"));
+
+        let err = parse("{\"a\":1,,\"b\":2}").unwrap_err();
+        assert_eq!(err, ParseError::new(UnexpectedComma, Span::new(7, 8)));
+    }
+
+    #[test]
+    fn it_reports_a_leading_comma() {
+        let err = parse("[,1]").unwrap_err();
+        assert_eq!(err, ParseError::new(UnexpectedComma, Span::new(1, 2)));
+    }
+
+    #[test]
+    fn it_round_trips_exponent_literals() {
+        let node = parse("[1e3, 2.5e2]").unwrap();
+        assert_eq!(node.to_string(), "[1000.0,250.0]");
+        assert_eq!(parse(&node.to_string()), Ok(node));
+
+        let mut options = ParseOptions::default();
+        options.lexer.integral_exponents_as_int = true;
+        let node = parse_with("[1e3, 2.5e2]", &options).unwrap();
+        assert_eq!(node.to_string(), "[1000,250.0]");
+        assert_eq!(parse(&node.to_string()), Ok(node));
+    }
+
+    #[test]
+    fn it_reports_in_bounds_spans_for_bad_strings() {
+        let input = "  \"abc\\";
+        let err = parse(input).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(InvalidStr(StrError::Unterminated), Span::new(7, 7))
+        );
+
+        let input = "[1, \"abc\\q\"]";
+        let err = parse(input).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(InvalidStr(StrError::InvalidEscape), Span::new(8, 8))
+        );
+        assert!(err.span.base <= input.len());
+    }
+
+    #[test]
+    fn it_reports_backslash_positions_in_strings() {
+        let err = parse("[\"abc\\\"]").unwrap_err();
+        let expected = ParseError::new(InvalidStr(StrError::Unterminated), Span::new(8, 8));
+        assert_eq!(err, expected);
+
+        assert_eq!(
+            parse("[\"abc\\\\\"]"),
+            Ok(Node::Array(vec![Node::Str("abc\\".into())]))
+        );
+
+        let err = parse("[\"ab\\ c\"]").unwrap_err();
+        let expected = ParseError::new(InvalidStr(StrError::InvalidEscape), Span::new(4, 4));
+        assert_eq!(err, expected);
+
+        // Escapes decode to fewer bytes than they take in the source.
+        let err = parse("[\"\\u00e9\\n\\\"é\\q\"]").unwrap_err();
+        let expected = ParseError::new(InvalidStr(StrError::InvalidEscape), Span::new(14, 14));
+        assert_eq!(err, expected);
+    }
+
+    #[test]
+    fn it_reports_non_string_keys() {
+        let err = parse("{1:2}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(NonStringKey(token::Int(1)), Span::new(1, 2))
+        );
+        assert_eq!(
+            err.hint().unwrap(),
+            "object keys must be double-quoted strings"
+        );
+
+        let err = parse("{true:1}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(NonStringKey(token::True), Span::new(1, 5))
+        );
+
+        let err = parse("{\"a\": 1, null:1}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(NonStringKey(token::Null), Span::new(9, 13))
+        );
+
+        let err = parse("{name:1}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(
+                NonStringKey(token::InvalidIdent("name".into())),
+                Span::new(1, 5)
+            )
+        );
+
+        // Only things that could be meant as a key are reported as one.
+        let err = parse("{\"a\":1,]").unwrap_err();
+        let kind = MismatchedDelimiter {
+            opened: token::OpenBracket,
+            found: token::CloseSquare,
+        };
+        assert_eq!(err, ParseError::new(kind, Span::new(7, 8)));
+        let err = parse("{\"a\":1,[").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(UnexpectedToken(token::OpenSquare), Span::new(7, 8))
+        );
+    }
+
+    #[test]
+    fn it_reports_leading_zeros_with_strict_numbers() {
+        let mut options = ParseOptions::default();
+        options.lexer.strict_numbers = true;
+        let err = parse_with("[1, 007]", &options).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(InvalidInt("007".into()), Span::new(4, 7))
+        );
+        assert_eq!(parse_with("[0, 0.5]", &options), parse("[0, 0.5]"));
+    }
+
+    #[test]
+    fn it_reports_mismatched_delimiters() {
+        let err = parse("[1}").unwrap_err();
+        let kind = MismatchedDelimiter {
+            opened: token::OpenSquare,
+            found: token::CloseBracket,
+        };
+        assert_eq!(err, ParseError::new(kind, Span::new(2, 3)));
+        assert_eq!(err.hint().unwrap(), "`[` must be closed by `]`");
+
+        let err = parse("{\"a\":1]").unwrap_err();
+        let kind = MismatchedDelimiter {
+            opened: token::OpenBracket,
+            found: token::CloseSquare,
+        };
+        assert_eq!(err, ParseError::new(kind, Span::new(6, 7)));
+        assert_eq!(err.hint().unwrap(), "`{` must be closed by `}`");
+
+        assert!(matches!(
+            parse("[}").unwrap_err().kind,
+            MismatchedDelimiter { .. }
+        ));
+        assert!(matches!(
+            parse("{]").unwrap_err().kind,
+            MismatchedDelimiter { .. }
+        ));
+    }
+
+    #[test]
+    fn it_caps_the_number_of_tokens() {
+        let options = ParseOptions {
+            max_tokens: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(parse_with("[1, 2]", &options), parse("[1, 2]"));
+
+        let err = parse_with("[1, 2, 3]", &options).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyTokens, Span::new(7, 8)));
+        assert!(parse("[1, 2, 3]").is_ok());
+    }
+
+    #[test]
+    fn it_caps_the_number_of_members_and_elements() {
+        let options = ParseOptions {
+            max_object_members: Some(2),
+            max_array_elements: Some(2),
+            ..Default::default()
+        };
+        assert!(parse_with("{\"a\": [1, 2], \"b\": {}}", &options).is_ok());
+
+        let err = parse_with("{\"a\":1,\"b\":2,\"c\":3}", &options).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyMembers, Span::new(0, 13)));
+
+        let err = parse_with("[[1,2,3]]", &options).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyElements, Span::new(1, 6)));
+    }
+
+    #[test]
+    fn it_enforces_caps_of_zero_and_one() {
+        let zero = ParseOptions {
+            max_object_members: Some(0),
+            max_array_elements: Some(0),
+            ..Default::default()
+        };
+        assert!(parse_with("{\"a\": [], \"b\": {}}", &zero).is_err());
+        assert_eq!(parse_with("[]", &zero), Ok(Node::Array(vec![])));
+        assert_eq!(parse_with("{}", &zero), Ok(Node::Object(vec![])));
+        let err = parse_with("[1,2,3]", &zero).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyElements, Span::new(0, 1)));
+        let err = parse_with("{\"a\":1}", &zero).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyMembers, Span::new(0, 1)));
+
+        let one = ParseOptions {
+            max_object_members: Some(1),
+            max_array_elements: Some(1),
+            ..Default::default()
+        };
+        assert!(parse_with("{\"a\": [{\"b\": 1}]}", &one).is_ok());
+        let err = parse_with("[1,2]", &one).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyElements, Span::new(0, 3)));
+        let err = parse_with("{\"a\":1,\"b\":2}", &one).unwrap_err();
+        assert_eq!(err, ParseError::new(TooManyMembers, Span::new(0, 7)));
+    }
+
+    #[test]
+    fn it_parses_invalid_utf8_lossily() {
+        let input = b"{\"name\": \"Adr\xFFien\"}";
+        let expected = Node::Object(vec![("name".into(), Node::Str("Adr\u{FFFD}ien".into()))]);
+        assert_eq!(parse_bytes_lossy(input), Ok(expected));
+        assert!(parse_bytes_lossy(b"[1, \xFF]").is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn it_keeps_the_raw_text_of_numbers() {
+        use crate::spanned::{parse_spanned, SpannedValue};
+
+        let input = "{\"pi\": 3.14159265358979323846264338327950288, \"n\": -12}";
+        let (node, raw) = parse_with_raw(input).unwrap();
+        assert_eq!(node, parse(input).unwrap());
+        assert_eq!(raw.len(), 2);
+
+        let SpannedValue::Object(members) = parse_spanned(input).unwrap().value else {
+            unreachable!()
+        };
+        let texts: Vec<_> = members
+            .iter()
+            .map(|(_, value)| raw[&value.span].as_str())
+            .collect();
+        assert_eq!(texts, ["3.14159265358979323846264338327950288", "-12"]);
+    }
+
+    #[test]
+    fn it_hints_at_misspelled_keywords() {
+        let hint = |input| parse(input).unwrap_err().hint();
+        assert_eq!(hint("True").unwrap(), "did you mean `true`?");
+        assert_eq!(hint("[FALSE]").unwrap(), "did you mean `false`?");
+        assert_eq!(hint("{\"a\": nul}").unwrap(), "did you mean `null`?");
+        assert_eq!(hint("Null").unwrap(), "did you mean `null`?");
+        assert_eq!(hint("fasle"), None);
+        assert_eq!(hint("undefined"), None);
+    }
+
+    #[test]
+    fn it_reports_expected_values() {
+        let err = parse("{\"a\":}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedValue(token::CloseBracket), Span::new(5, 6))
+        );
+        assert!(err.hint().is_some());
+
+        let err = parse("[").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedValue(token::Eof), Span::new(1, 1))
+        );
+
+        let err = parse("[1,]").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedValue(token::CloseSquare), Span::new(3, 4))
+        );
+
+        let err = parse("").unwrap_err();
+        assert_eq!(err, ParseError::new(EmptyInput, Span::new(0, 0)));
+        assert!(err.hint().is_some());
+    }
+
+    #[test]
+    fn it_applies_strictness_profiles() {
+        let rfc = StrictnessProfile::Rfc8259.options();
+        assert_eq!(
+            parse_with("", &rfc),
+            Err(ParseError::new(EmptyInput, Span::new(0, 0)))
+        );
+        assert_eq!(
+            parse_with(" \n", &rfc),
+            Err(ParseError::new(EmptyInput, Span::new(0, 2)))
+        );
+        assert_eq!(
+            parse_with("1 2", &rfc),
+            Err(ParseError::new(
+                UnexpectedContinuation(token::Int(2)),
+                Span::new(2, 3)
+            ))
+        );
+        assert_eq!(parse_with("42", &rfc), Ok(Node::Int(42)));
+
+        let lenient = StrictnessProfile::Lenient.options();
+        assert_eq!(parse_with("", &lenient), Ok(Node::Null));
+        assert_eq!(parse_with("1 2", &lenient), Ok(Node::Int(1)));
+        assert_eq!(parse_with("42", &lenient), Ok(Node::Int(42)));
+
+        let embeddable = StrictnessProfile::Embeddable.options();
+        assert_eq!(
+            parse_with("", &embeddable),
+            Err(ParseError::new(EmptyInput, Span::new(0, 0)))
+        );
+        assert_eq!(
+            parse_with("1 2", &embeddable),
+            Err(ParseError::new(
+                ExpectedContainer(token::Int(1)),
+                Span::new(0, 1)
+            ))
+        );
+        assert_eq!(
+            parse_with("42", &embeddable),
+            Err(ParseError::new(
+                ExpectedContainer(token::Int(42)),
+                Span::new(0, 2)
+            ))
+        );
+        assert_eq!(
+            parse_with("[42] and then some", &embeddable),
+            Ok(Node::Array(vec![Node::Int(42)]))
+        );
+    }
+
+    #[test]
+    fn it_accepts_equals_separators_when_allowed() {
+        let err = parse("{\"a\"=1}").unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(ExpectedColon(token::Unknown("=".into())), Span::new(4, 5))
+        );
+
+        let options = ParseOptions {
+            allow_equals_separator: true,
+            ..Default::default()
+        };
+        let (node, warnings) = parse_with_warnings("{\"a\"=1, \"b\": 2}", &options).unwrap();
+        assert_eq!(node, parse("{\"a\": 1, \"b\": 2}").unwrap());
+        let warning = ParseWarning {
+            kind: ParseWarningKind::EqualsSeparator,
+            span: Span::new(4, 5),
+        };
+        assert_eq!(warnings, [warning]);
+    }
+
+    #[test]
+    fn it_slices_the_source_of_errors() {
+        let input = "{\"clé\": vrai}";
+        let err = parse(input).unwrap_err();
+        assert_eq!(err.source_slice(input), "vrai");
+        assert_eq!(err.source_slice("{"), "");
+    }
+
+    #[test]
+    fn it_skips_a_header_line_when_allowed() {
+        let input = "#!/usr/bin/env jason\n{\"a\": [1, 2]}";
+        let err = parse(input).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(UnknownToken("#".into()), Span::new(0, 1))
+        );
+
+        let options = ParseOptions {
+            lexer: LexOptions {
+                skip_header_line: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let node = parse_with(input, &options).unwrap();
+        assert_eq!(node, parse("{\"a\": [1, 2]}").unwrap());
+        let node = parse_with("// generated\r\n[true]", &options).unwrap();
+        assert_eq!(node, Node::Array(vec![Node::True]));
+
+        // Spans still point into the whole input.
+        let err = parse_with("# config\n{1}", &options).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::new(NonStringKey(token::Int(1)), Span::new(10, 11))
+        );
+        // Only a header at the very start is skipped.
+        assert!(parse_with(" # config\n{}", &options).is_err());
+    }
+
+    #[test]
+    fn it_limits_whitespace_runs() {
+        let options = ParseOptions {
+            lexer: LexOptions {
+                max_whitespace_run: Some(4),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let input = "[1,    2,\n\n\n\n\n3]";
+        assert!(parse(input).is_ok());
+        let err = parse_with(input, &options).unwrap_err();
+        assert_eq!(err, ParseError::new(WhitespaceRunTooLong, Span::new(9, 14)));
+        let err = parse_with("{\"a\"      : 1}", &options).unwrap_err();
+        assert_eq!(err, ParseError::new(WhitespaceRunTooLong, Span::new(4, 9)));
+        let err = parse_with("[1]      ", &options).unwrap_err();
+        assert_eq!(err, ParseError::new(WhitespaceRunTooLong, Span::new(3, 8)));
+    }
+
+    #[test]
+    fn it_drives_a_custom_handler() {
+        use alloc::collections::BTreeMap;
+
+        #[derive(Default)]
+        struct KeyCounter(BTreeMap<String, usize>);
+
+        impl Handler for KeyCounter {
+            type Key = ();
+            type Value = ();
+
+            fn key(&mut self, key: String) {
+                *self.0.entry(key).or_default() += 1;
+            }
+
+            fn scalar(&mut self, _: Node) {}
+
+            fn object(&mut self, _: Vec<((), ())>) {}
+
+            fn array(&mut self, _: Vec<()>) {}
+        }
+
+        let input = r#"[{"id": 1, "tags": {"id": 2}}, {"id": 3, "name": "x"}]"#;
+        let mut counter = KeyCounter::default();
+        parse_with_handler(input, &ParseOptions::default(), &mut counter).unwrap();
+        let counts: Vec<_> = counter.0.into_iter().collect();
+        assert_eq!(
+            counts,
+            [("id".into(), 3), ("name".into(), 1), ("tags".into(), 1)]
+        );
+    }
+}
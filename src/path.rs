@@ -0,0 +1,74 @@
+use crate::ast::Node;
+
+/// A single step into a node: an object key or an array index.
+pub trait PathStep {
+    fn step<'a>(&self, node: &'a Node) -> Option<&'a Node>;
+}
+
+impl PathStep for &str {
+    fn step<'a>(&self, node: &'a Node) -> Option<&'a Node> {
+        node.get(self)
+    }
+}
+
+impl PathStep for usize {
+    fn step<'a>(&self, node: &'a Node) -> Option<&'a Node> {
+        match node {
+            Node::Array(elements) => elements.get(*self),
+            _ => None,
+        }
+    }
+}
+
+/// Fluent read-only navigation through a `Node`, started with `Node::at`.
+///
+/// Once a step fails to resolve every following step yields `None` too.
+#[derive(Debug, Clone, Copy)]
+pub struct PathCursor<'a> {
+    node: Option<&'a Node>,
+}
+
+impl<'a> PathCursor<'a> {
+    /// Steps into the member with the given key or the element at the given
+    /// index.
+    pub fn at<S: PathStep>(self, step: S) -> PathCursor<'a> {
+        PathCursor {
+            node: self.node.and_then(|node| step.step(node)),
+        }
+    }
+
+    /// The node reached so far, if every step resolved.
+    pub fn node(self) -> Option<&'a Node> {
+        self.node
+    }
+}
+
+impl Node {
+    /// Starts fluent navigation, e.g. `node.at("items").at(0).at("id").node()`.
+    pub fn at<S: PathStep>(&self, step: S) -> PathCursor<'_> {
+        PathCursor { node: Some(self) }.at(step)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::Node;
+    use crate::parser::parse;
+
+    const SAMPLE: &str = "{\"root\": {\"type\": \"list\", \"children\": [{\"type\": \"item\", \"value\": 1}, {\"type\": \"list\", \"children\": []}]}}";
+
+    #[test]
+    fn it_navigates_to_a_leaf() {
+        let node = parse(SAMPLE).unwrap();
+        let leaf = node.at("root").at("children").at(0).at("type").node();
+        assert_eq!(leaf, Some(&Node::Str("item".into())));
+    }
+
+    #[test]
+    fn it_navigates_to_a_missing_path() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.at("root").at("children").at(2).node(), None);
+        assert_eq!(node.at("root").at(0).at("type").node(), None);
+        assert_eq!(node.at("nope").at("children").node(), None);
+    }
+}
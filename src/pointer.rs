@@ -0,0 +1,644 @@
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::String,
+    vec,
+    vec::Vec,
+};
+use core::{cmp::Ordering, convert::Infallible, mem};
+
+use crate::ast::{total_cmp, Node};
+use crate::ser::push_display;
+
+/// Error returned by pointer based edits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PointerError {
+    /// The pointer is not empty and doesn't start with `/`.
+    Malformed,
+    /// No node exists at the pointer (or at the parent of its target).
+    NotFound,
+    /// The node at the pointer (or the parent of its target) has the wrong
+    /// type.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// An array reference token isn't `-` or an index within bounds.
+    InvalidIndex(String),
+}
+
+/// Splits a JSON Pointer (RFC 6901) into its unescaped reference tokens.
+///
+/// The empty pointer refers to the whole document and yields no tokens. Any
+/// other pointer must start with `/`, otherwise `None` is returned.
+pub(crate) fn reference_tokens(ptr: &str) -> Option<Vec<String>> {
+    if ptr.is_empty() {
+        return Some(vec![]);
+    }
+    let rest = ptr.strip_prefix('/')?;
+    let tokens = rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    Some(tokens)
+}
+
+/// Escapes a reference token for use in a JSON Pointer, the inverse of the
+/// unescaping done by `reference_tokens`.
+pub(crate) fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Parses an array index reference token. Leading zeros are not allowed.
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    match token.as_bytes() {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', rest @ ..] if rest.iter().all(u8::is_ascii_digit) => token.parse().ok(),
+        _ => None,
+    }
+}
+
+fn try_fold_with_path<B, E, F>(node: &Node, path: &mut String, init: B, f: &mut F) -> Result<B, E>
+where
+    F: FnMut(B, &str, &Node) -> Result<B, E>,
+{
+    let mut acc = f(init, path, node)?;
+    let len = path.len();
+    match node {
+        Node::Object(members) => {
+            for (key, value) in members {
+                push_display(path, format_args!("/{}", escape_token(key)));
+                acc = try_fold_with_path(value, path, acc, f)?;
+                path.truncate(len);
+            }
+        }
+        Node::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                push_display(path, format_args!("/{}", i));
+                acc = try_fold_with_path(element, path, acc, f)?;
+                path.truncate(len);
+            }
+        }
+        _ => (),
+    }
+    Ok(acc)
+}
+
+fn child<'a>(node: &'a Node, token: &str) -> Option<&'a Node> {
+    match node {
+        Node::Object(members) => members
+            .iter()
+            .find(|(key, _)| key == token)
+            .map(|(_, value)| value),
+        Node::Array(elements) => elements.get(array_index(token)?),
+        _ => None,
+    }
+}
+
+fn child_mut<'a>(node: &'a mut Node, token: &str) -> Option<&'a mut Node> {
+    match node {
+        Node::Object(members) => members
+            .iter_mut()
+            .find(|(key, _)| key == token)
+            .map(|(_, value)| value),
+        Node::Array(elements) => elements.get_mut(array_index(token)?),
+        _ => None,
+    }
+}
+
+impl Node {
+    /// Looks up a node by JSON Pointer (RFC 6901), e.g. `"/items/0/name"`.
+    ///
+    /// If an object has duplicate keys, the first matching member is used.
+    pub fn pointer(&self, ptr: &str) -> Option<&Node> {
+        reference_tokens(ptr)?
+            .iter()
+            .try_fold(self, |node, token| child(node, token))
+    }
+
+    /// Mutable version of `pointer`.
+    pub fn pointer_mut(&mut self, ptr: &str) -> Option<&mut Node> {
+        reference_tokens(ptr)?
+            .iter()
+            .try_fold(self, |node, token| child_mut(node, token))
+    }
+
+    /// Sets the node at `ptr` to `value`, returning the node it replaced.
+    ///
+    /// The parent of the target must exist. A missing object member is
+    /// appended and the `-` token appends to an array, in which case `None`
+    /// is returned.
+    pub fn replace_at(&mut self, ptr: &str, value: Node) -> Result<Option<Node>, PointerError> {
+        let mut tokens = reference_tokens(ptr).ok_or(PointerError::Malformed)?;
+        let last = match tokens.pop() {
+            Some(last) => last,
+            None => return Ok(Some(mem::replace(self, value))),
+        };
+        let parent = tokens
+            .iter()
+            .try_fold(self, |node, token| child_mut(node, token))
+            .ok_or(PointerError::NotFound)?;
+        match parent {
+            Node::Object(members) => match members.iter_mut().find(|(key, _)| *key == last) {
+                Some((_, slot)) => Ok(Some(mem::replace(slot, value))),
+                None => {
+                    members.push((last, value));
+                    Ok(None)
+                }
+            },
+            Node::Array(elements) if last == "-" => {
+                elements.push(value);
+                Ok(None)
+            }
+            Node::Array(elements) => {
+                let slot = array_index(&last)
+                    .and_then(|i| elements.get_mut(i))
+                    .ok_or(PointerError::InvalidIndex(last))?;
+                Ok(Some(mem::replace(slot, value)))
+            }
+            other => Err(PointerError::TypeMismatch {
+                expected: "object or array",
+                found: other.type_name(),
+            }),
+        }
+    }
+
+    /// Reorders the members of the object at `ptr` to follow `key_order`.
+    ///
+    /// Members whose key isn't listed come after the listed ones, in their
+    /// original relative order. Duplicates of a listed key stay together in
+    /// that key's place, in their original relative order.
+    pub fn reorder_object(&mut self, ptr: &str, key_order: &[&str]) -> Result<(), PointerError> {
+        let tokens = reference_tokens(ptr).ok_or(PointerError::Malformed)?;
+        let node = tokens
+            .iter()
+            .try_fold(self, |node, token| child_mut(node, token))
+            .ok_or(PointerError::NotFound)?;
+        match node {
+            Node::Object(members) => {
+                // A stable sort keeps unlisted keys and duplicates in order.
+                members.sort_by_key(|(key, _)| {
+                    key_order
+                        .iter()
+                        .position(|k| k == key)
+                        .unwrap_or(key_order.len())
+                });
+                Ok(())
+            }
+            other => Err(PointerError::TypeMismatch {
+                expected: "object",
+                found: other.type_name(),
+            }),
+        }
+    }
+
+    /// Renames the first member `from` of the object at `ptr` to `to`, in
+    /// place, returning whether there was such a member.
+    ///
+    /// Any other member already named `to` is overwritten, i.e. removed, so
+    /// the renamed member is the only one with that key afterwards.
+    pub fn rename_key(&mut self, ptr: &str, from: &str, to: &str) -> Result<bool, PointerError> {
+        let tokens = reference_tokens(ptr).ok_or(PointerError::Malformed)?;
+        let node = tokens
+            .iter()
+            .try_fold(self, |node, token| child_mut(node, token))
+            .ok_or(PointerError::NotFound)?;
+        let members = match node {
+            Node::Object(members) => members,
+            other => {
+                return Err(PointerError::TypeMismatch {
+                    expected: "object",
+                    found: other.type_name(),
+                })
+            }
+        };
+        let index = match members.iter().position(|(key, _)| key == from) {
+            Some(index) => index,
+            None => return Ok(false),
+        };
+        let mut i = 0;
+        members.retain(|(key, _)| {
+            let keep = key != to || i == index;
+            i += 1;
+            keep
+        });
+        if let Some((key, _)) = members.iter_mut().find(|(key, _)| key == from) {
+            *key = to.into();
+        }
+        Ok(true)
+    }
+
+    /// Sorts the objects of the array at `ptr` by the value of their `key`
+    /// member, e.g. to list named records in a deterministic order.
+    ///
+    /// Values are ordered like `Node`'s `PartialOrd`, floats by
+    /// `f64::total_cmp`. Elements lacking `key`, that aren't objects or whose
+    /// value is an object or an array sort last. The sort is stable.
+    pub fn sort_array_at_by_key(&mut self, ptr: &str, key: &str) -> Result<(), PointerError> {
+        let tokens = reference_tokens(ptr).ok_or(PointerError::Malformed)?;
+        let node = tokens
+            .iter()
+            .try_fold(self, |node, token| child_mut(node, token))
+            .ok_or(PointerError::NotFound)?;
+        match node {
+            Node::Array(elements) => {
+                fn sort_key<'n>(element: &'n Node, key: &str) -> Option<&'n Node> {
+                    match element.get(key) {
+                        Some(Node::Object(_) | Node::Array(_)) => None,
+                        value => value,
+                    }
+                }
+                elements.sort_by(|a, b| match (sort_key(a, key), sort_key(b, key)) {
+                    (Some(a), Some(b)) => total_cmp(a, b),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                });
+                Ok(())
+            }
+            other => Err(PointerError::TypeMismatch {
+                expected: "array",
+                found: other.type_name(),
+            }),
+        }
+    }
+
+    /// Moves the node at `ptr` out, leaving `Null` in its place.
+    pub fn take_at(&mut self, ptr: &str) -> Option<Node> {
+        self.pointer_mut(ptr).map(Node::take)
+    }
+
+    /// Returns true if `ptr` resolves to a node, including a `Null` one.
+    pub fn path_exists(&self, ptr: &str) -> bool {
+        self.pointer(ptr).is_some()
+    }
+
+    /// Clones every leaf of the tree into a map keyed by its JSON Pointer,
+    /// e.g. to diff two documents path by path.
+    ///
+    /// Leaves are scalars and empty containers, so `{"a": {}, "b": [1]}`
+    /// maps `/a` to an empty object and `/b/0` to `1`. A scalar root is
+    /// mapped from `""`. Paths sort as strings, so `/10` comes before `/2`.
+    /// Members shadowed by an earlier duplicate key are left out, as they
+    /// can't be reached with `pointer`.
+    pub fn to_flat_map(&self) -> BTreeMap<String, Node> {
+        // Members shadowed by an earlier duplicate key, and everything nested
+        // in them.
+        let mut shadowed: BTreeSet<*const Node> = BTreeSet::new();
+        let flattened = self.try_fold(BTreeMap::new(), |mut map, path, node| {
+            let hidden = shadowed.contains(&(node as *const Node));
+            match node {
+                Node::Object(members) if !members.is_empty() => {
+                    // `pointer` resolves a key to its first member, so skip the
+                    // rest.
+                    let mut seen = BTreeSet::new();
+                    for (key, value) in members {
+                        if hidden || !seen.insert(key.as_str()) {
+                            shadowed.insert(value);
+                        }
+                    }
+                }
+                Node::Array(elements) if !elements.is_empty() => {
+                    if hidden {
+                        shadowed.extend(elements.iter().map(|e| e as *const Node));
+                    }
+                }
+                leaf => {
+                    if !hidden {
+                        map.insert(String::from(path), leaf.clone());
+                    }
+                }
+            }
+            Ok::<_, Infallible>(map)
+        });
+        let Ok(map) = flattened;
+        map
+    }
+
+    /// Calls `f` with every node in the tree, including this one, and its
+    /// JSON Pointer, in the same order as `iter_paths`.
+    pub fn walk_with_path<F: FnMut(&str, &Node)>(&self, f: &mut F) {
+        let walked = self.try_fold((), |(), path, node| {
+            f(path, node);
+            Ok::<_, Infallible>(())
+        });
+        let Ok(()) = walked;
+    }
+
+    /// Folds every node in the tree, including this one, into an accumulator,
+    /// visiting them in the same order as `iter_paths`.
+    ///
+    /// `f` is given the accumulator, the node's JSON Pointer and the node,
+    /// and stops the fold early by returning an error.
+    pub fn try_fold<B, E, F>(&self, init: B, mut f: F) -> Result<B, E>
+    where
+        F: FnMut(B, &str, &Node) -> Result<B, E>,
+    {
+        try_fold_with_path(self, &mut String::new(), init, &mut f)
+    }
+
+    /// Returns the node at the first of `pointers` that resolves to anything
+    /// but `Null`, e.g. for fallback chains like `["/a/b/c", "/a/b/d"]`.
+    pub fn coalesce(&self, pointers: &[&str]) -> Option<&Node> {
+        pointers
+            .iter()
+            .filter_map(|ptr| self.pointer(ptr))
+            .find(|node| **node != Node::Null)
+    }
+
+    /// Iterates over every node in the tree, including this one and every
+    /// container, along with its JSON Pointer.
+    ///
+    /// Nodes are visited depth-first in document order, starting with
+    /// `("", self)`. Keys are escaped per RFC 6901, so every pointer
+    /// resolves back to its node with `pointer`, except for members shadowed
+    /// by an earlier duplicate key.
+    pub fn iter_paths(&self) -> impl Iterator<Item = (String, &Node)> {
+        let mut stack = vec![(String::new(), self)];
+        core::iter::from_fn(move || {
+            let (path, node) = stack.pop()?;
+            match node {
+                Node::Object(members) => {
+                    let children = members
+                        .iter()
+                        .map(|(key, value)| (format!("{}/{}", path, escape_token(key)), value));
+                    stack.extend(children.rev());
+                }
+                Node::Array(elements) => {
+                    let children = elements
+                        .iter()
+                        .enumerate()
+                        .map(|(i, element)| (format!("{}/{}", path, i), element));
+                    stack.extend(children.rev());
+                }
+                _ => (),
+            }
+            Some((path, node))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PointerError;
+    use crate::ast::Node;
+    use crate::parser::parse;
+
+    const SAMPLE: &str = "{\"a\": {\"b\": [1, {\"c\": null}]}, \"x/y\": 2, \"m~n\": 3, \"\": 4}";
+
+    #[test]
+    fn it_resolves_pointers() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.pointer(""), Some(&node));
+        assert_eq!(node.pointer("/a/b/0"), Some(&Node::Int(1)));
+        assert_eq!(node.pointer("/a/b/1/c"), Some(&Node::Null));
+        assert_eq!(node.pointer("/x~1y"), Some(&Node::Int(2)));
+        assert_eq!(node.pointer("/m~0n"), Some(&Node::Int(3)));
+        assert_eq!(node.pointer("/"), Some(&Node::Int(4)));
+    }
+
+    #[test]
+    fn it_rejects_bad_pointers() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.pointer("a"), None);
+        assert_eq!(node.pointer("/a/b/01"), None);
+        assert_eq!(node.pointer("/a/b/2"), None);
+        assert_eq!(node.pointer("/a/b/-"), None);
+        assert_eq!(node.pointer("/a/b/0/c"), None);
+    }
+
+    #[test]
+    fn it_resolves_mutable_pointers() {
+        let mut node = parse(SAMPLE).unwrap();
+        *node.pointer_mut("/a/b/0").unwrap() = Node::True;
+        assert_eq!(node.pointer("/a/b/0"), Some(&Node::True));
+    }
+
+    #[test]
+    fn it_checks_path_existence() {
+        let node = parse(SAMPLE).unwrap();
+        assert!(node.path_exists("/a/b/0"));
+        assert!(node.path_exists("/a/b/1/c"));
+        assert!(!node.path_exists("/a/b/1/d"));
+        assert!(!node.path_exists("/z"));
+    }
+
+    #[test]
+    fn it_replaces_a_nested_value() {
+        let mut node = parse(SAMPLE).unwrap();
+        assert_eq!(
+            node.replace_at("/a/b/1/c", Node::True),
+            Ok(Some(Node::Null))
+        );
+        assert_eq!(node.pointer("/a/b/1/c"), Some(&Node::True));
+        assert_eq!(node.replace_at("/a/b/1/d", Node::False), Ok(None));
+        assert_eq!(node.pointer("/a/b/1/d"), Some(&Node::False));
+    }
+
+    #[test]
+    fn it_appends_to_an_array() {
+        let mut node = parse(SAMPLE).unwrap();
+        assert_eq!(node.replace_at("/a/b/-", Node::Int(2)), Ok(None));
+        assert_eq!(node.pointer("/a/b/2"), Some(&Node::Int(2)));
+    }
+
+    #[test]
+    fn it_replaces_the_root() {
+        let mut node = Node::Int(1);
+        assert_eq!(node.replace_at("", Node::Null), Ok(Some(Node::Int(1))));
+        assert_eq!(node, Node::Null);
+    }
+
+    #[test]
+    fn it_fails_to_replace_without_a_parent() {
+        let mut node = parse(SAMPLE).unwrap();
+        assert_eq!(
+            node.replace_at("a", Node::Null),
+            Err(PointerError::Malformed)
+        );
+        assert_eq!(
+            node.replace_at("/z/y", Node::Null),
+            Err(PointerError::NotFound)
+        );
+        assert_eq!(
+            node.replace_at("/a/b/5", Node::Null),
+            Err(PointerError::InvalidIndex("5".into()))
+        );
+        assert_eq!(
+            node.replace_at("/a/b/0/c", Node::Null),
+            Err(PointerError::TypeMismatch {
+                expected: "object or array",
+                found: "number"
+            })
+        );
+    }
+
+    #[test]
+    fn it_takes_a_nested_node() {
+        let mut node = parse(SAMPLE).unwrap();
+        let taken = node.take_at("/a/b");
+        assert_eq!(taken, Some(parse("[1, {\"c\": null}]").unwrap()));
+        assert_eq!(node.pointer("/a/b"), Some(&Node::Null));
+        assert_eq!(node.take_at("/a/b/0"), None);
+    }
+
+    #[test]
+    fn it_takes_the_root() {
+        let mut node = parse("[1]").unwrap();
+        assert_eq!(node.take(), Node::Array(vec![Node::Int(1)]));
+        assert_eq!(node, Node::Null);
+    }
+
+    #[test]
+    fn it_iterates_over_every_path() {
+        let node = parse(SAMPLE).unwrap();
+        let paths: Vec<_> = node.iter_paths().map(|(path, _)| path).collect();
+        assert_eq!(
+            paths,
+            ["", "/a", "/a/b", "/a/b/0", "/a/b/1", "/a/b/1/c", "/x~1y", "/m~0n", "/"]
+        );
+        for (path, value) in node.iter_paths() {
+            assert_eq!(node.pointer(&path), Some(value));
+        }
+    }
+
+    #[test]
+    fn it_coalesces_pointers() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(
+            node.coalesce(&["/a/z", "/a/b/1/c", "/x~1y", "/m~0n"]),
+            Some(&Node::Int(2))
+        );
+        assert_eq!(node.coalesce(&["/a/z", "/a/b/1/c"]), None);
+        assert_eq!(node.coalesce(&[]), None);
+    }
+
+    #[test]
+    fn it_reorders_an_object() {
+        let mut node =
+            parse("{\"cfg\": {\"b\": 1, \"x\": 2, \"a\": 3, \"y\": 4, \"c\": 5}}").unwrap();
+        assert_eq!(node.reorder_object("/cfg", &["c", "a", "b", "z"]), Ok(()));
+        let expected =
+            parse("{\"cfg\": {\"c\": 5, \"a\": 3, \"b\": 1, \"x\": 2, \"y\": 4}}").unwrap();
+        assert_eq!(node, expected);
+
+        let mut node = parse("{\"a\": 1, \"b\": 2, \"a\": 3, \"c\": 4}").unwrap();
+        assert_eq!(node.reorder_object("", &["b", "a"]), Ok(()));
+        let expected = parse("{\"b\": 2, \"a\": 1, \"a\": 3, \"c\": 4}").unwrap();
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn it_renames_a_key() {
+        let mut node = parse("{\"cfg\": {\"a\": 1, \"old\": 2, \"c\": 3}}").unwrap();
+        assert_eq!(node.rename_key("/cfg", "old", "new"), Ok(true));
+        assert_eq!(
+            node,
+            parse("{\"cfg\": {\"a\": 1, \"new\": 2, \"c\": 3}}").unwrap()
+        );
+
+        assert_eq!(node.rename_key("/cfg", "missing", "x"), Ok(false));
+        assert_eq!(
+            node,
+            parse("{\"cfg\": {\"a\": 1, \"new\": 2, \"c\": 3}}").unwrap()
+        );
+
+        assert_eq!(node.rename_key("/cfg", "c", "a"), Ok(true));
+        assert_eq!(node, parse("{\"cfg\": {\"new\": 2, \"a\": 3}}").unwrap());
+        assert_eq!(node.rename_key("/cfg", "a", "a"), Ok(true));
+        assert_eq!(node, parse("{\"cfg\": {\"new\": 2, \"a\": 3}}").unwrap());
+
+        assert_eq!(
+            node.rename_key("/cfg/a", "x", "y"),
+            Err(PointerError::TypeMismatch {
+                expected: "object",
+                found: "number"
+            })
+        );
+    }
+
+    #[test]
+    fn it_fails_to_reorder_a_non_object() {
+        let mut node = parse(SAMPLE).unwrap();
+        assert_eq!(
+            node.reorder_object("/a/b", &[]),
+            Err(PointerError::TypeMismatch {
+                expected: "object",
+                found: "array"
+            })
+        );
+        assert_eq!(node.reorder_object("/z", &[]), Err(PointerError::NotFound));
+        assert_eq!(node.reorder_object("z", &[]), Err(PointerError::Malformed));
+    }
+
+    #[test]
+    fn it_walks_with_paths() {
+        let node = parse(SAMPLE).unwrap();
+        let mut visited = vec![];
+        node.walk_with_path(&mut |path, node| visited.push((path.to_owned(), node.type_name())));
+        assert_eq!(visited.len(), 9);
+        assert_eq!(visited[0], ("".to_owned(), "object"));
+        assert!(visited.contains(&("/a/b".to_owned(), "array")));
+        assert!(visited.contains(&("/a/b/1/c".to_owned(), "null")));
+        assert!(visited.contains(&("/x~1y".to_owned(), "number")));
+        assert!(visited.contains(&("/m~0n".to_owned(), "number")));
+        let paths: Vec<_> = node.iter_paths().map(|(path, _)| path).collect();
+        assert!(visited.iter().map(|(path, _)| path).eq(paths.iter()));
+    }
+
+    #[test]
+    fn it_flattens_into_a_map() {
+        let node =
+            parse("{\"b\": [1, {\"c\": null}], \"a\": {}, \"d\": [], \"b\": {\"e\": 2}}").unwrap();
+        let flat: Vec<_> = node.to_flat_map().into_iter().collect();
+        assert_eq!(
+            flat,
+            [
+                ("/a".to_owned(), Node::Object(vec![])),
+                ("/b/0".to_owned(), Node::Int(1)),
+                ("/b/1/c".to_owned(), Node::Null),
+                ("/d".to_owned(), Node::Array(vec![])),
+            ]
+        );
+        let flat = Node::True.to_flat_map();
+        assert_eq!(flat.get(""), Some(&Node::True));
+    }
+
+    #[test]
+    fn it_folds_over_the_tree() {
+        let node = parse("{\"a\": {\"b\": [1, 2]}, \"c\": 3, \"d\": null}").unwrap();
+        let sum: Result<isize, ()> = node.try_fold(0, |sum, path, node| match node {
+            Node::Int(i) if path.starts_with("/a/") => Ok(sum + i),
+            _ => Ok(sum),
+        });
+        assert_eq!(sum, Ok(3));
+
+        let mut visited = 0;
+        let result = node.try_fold(0, |sum, path, node| {
+            visited += 1;
+            match node {
+                Node::Int(i) => Ok(sum + i),
+                Node::Null => Err(path.to_owned()),
+                _ => Ok(sum),
+            }
+        });
+        assert_eq!(result, Err("/d".to_owned()));
+        assert_eq!(visited, 7);
+    }
+
+    #[test]
+    fn it_sorts_an_array_by_key() {
+        let mut node = parse("{\"users\": [{\"id\": 3, \"n\": \"c\"}, {\"n\": \"x\"}, {\"id\": 1, \"n\": \"a\"}, 7, {\"id\": 2, \"n\": \"b\"}]}").unwrap();
+        assert_eq!(node.sort_array_at_by_key("/users", "id"), Ok(()));
+        let expected = parse("{\"users\": [{\"id\": 1, \"n\": \"a\"}, {\"id\": 2, \"n\": \"b\"}, {\"id\": 3, \"n\": \"c\"}, {\"n\": \"x\"}, 7]}").unwrap();
+        assert_eq!(node, expected);
+
+        assert_eq!(
+            node.sort_array_at_by_key("", "id"),
+            Err(PointerError::TypeMismatch {
+                expected: "array",
+                found: "object"
+            })
+        );
+    }
+}
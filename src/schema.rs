@@ -0,0 +1,178 @@
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+
+use crate::ast::Node;
+use crate::pointer::escape_token;
+
+/// A minimal structural description of a document, checked by
+/// `Node::validate`.
+///
+/// This covers the common "these members, of these types" cases, it is not
+/// JSON Schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Schema {
+    /// An object with the given members, each with its schema and whether it
+    /// is required. Members not listed are allowed.
+    Object(Vec<(String, Schema, bool)>),
+    /// An array whose elements all match the schema.
+    Array(Box<Schema>),
+    String,
+    /// An `Int` or a `Float`.
+    Number,
+    Bool,
+    Null,
+    /// Any node at all.
+    Any,
+}
+
+impl Schema {
+    /// The JSON type this schema expects, as given by `Node::type_name`.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Schema::Object(_) => "object",
+            Schema::Array(_) => "array",
+            Schema::String => "string",
+            Schema::Number => "number",
+            Schema::Bool => "boolean",
+            Schema::Null => "null",
+            Schema::Any => "any",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// The node isn't of the type the schema expects.
+    TypeMismatch {
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// A required member is missing.
+    MissingMember,
+}
+
+/// A mismatch between a document and a `Schema`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationError {
+    /// JSON Pointer to the offending node, or to the missing member.
+    pub path: String,
+    pub kind: ValidationErrorKind,
+}
+
+impl Node {
+    /// Checks this node against `schema`, collecting every mismatch rather
+    /// than stopping at the first one.
+    pub fn validate(&self, schema: &Schema) -> Result<(), Vec<ValidationError>> {
+        let mut errors = vec![];
+        validate_into(self, schema, &mut String::new(), &mut errors);
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+fn validate_into(
+    node: &Node,
+    schema: &Schema,
+    path: &mut String,
+    errors: &mut Vec<ValidationError>,
+) {
+    match (schema, node) {
+        (Schema::Any, _)
+        | (Schema::String, Node::Str(_))
+        | (Schema::Number, Node::Int(_) | Node::Float(_))
+        | (Schema::Bool, Node::True | Node::False)
+        | (Schema::Null, Node::Null) => (),
+        (Schema::Object(fields), Node::Object(_)) => {
+            for (key, field, required) in fields {
+                let len = path.len();
+                path.push('/');
+                path.push_str(&escape_token(key));
+                match node.get(key) {
+                    Some(value) => validate_into(value, field, path, errors),
+                    None if *required => errors.push(ValidationError {
+                        path: path.clone(),
+                        kind: ValidationErrorKind::MissingMember,
+                    }),
+                    None => (),
+                }
+                path.truncate(len);
+            }
+        }
+        (Schema::Array(element), Node::Array(elements)) => {
+            for (i, value) in elements.iter().enumerate() {
+                let len = path.len();
+                path.push_str(&format!("/{}", i));
+                validate_into(value, element, path, errors);
+                path.truncate(len);
+            }
+        }
+        _ => errors.push(ValidationError {
+            path: path.clone(),
+            kind: ValidationErrorKind::TypeMismatch {
+                expected: schema.type_name(),
+                found: node.type_name(),
+            },
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Schema, ValidationError, ValidationErrorKind};
+    use crate::parser::parse;
+
+    fn person() -> Schema {
+        Schema::Object(vec![
+            ("name".into(), Schema::String, true),
+            ("age".into(), Schema::Number, true),
+            ("nickname".into(), Schema::String, false),
+            (
+                "tags".into(),
+                Schema::Array(Box::new(Schema::String)),
+                false,
+            ),
+        ])
+    }
+
+    #[test]
+    fn it_validates_a_matching_document() {
+        let node = parse("{\"name\": \"Adrien\", \"age\": 23, \"tags\": [\"a\"], \"extra\": null}")
+            .unwrap();
+        assert_eq!(node.validate(&person()), Ok(()));
+        assert_eq!(node.validate(&Schema::Any), Ok(()));
+    }
+
+    #[test]
+    fn it_reports_a_missing_required_member() {
+        let node = parse("{\"name\": \"Adrien\"}").unwrap();
+        let errors = vec![ValidationError {
+            path: "/age".into(),
+            kind: ValidationErrorKind::MissingMember,
+        }];
+        assert_eq!(node.validate(&person()), Err(errors));
+    }
+
+    #[test]
+    fn it_reports_every_type_mismatch() {
+        let node = parse("{\"name\": 1, \"age\": 23, \"tags\": [\"a\", true]}").unwrap();
+        let errors = vec![
+            ValidationError {
+                path: "/name".into(),
+                kind: ValidationErrorKind::TypeMismatch {
+                    expected: "string",
+                    found: "number",
+                },
+            },
+            ValidationError {
+                path: "/tags/1".into(),
+                kind: ValidationErrorKind::TypeMismatch {
+                    expected: "string",
+                    found: "boolean",
+                },
+            },
+        ];
+        assert_eq!(node.validate(&person()), Err(errors));
+    }
+}
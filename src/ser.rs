@@ -0,0 +1,764 @@
+use alloc::string::{String, ToString};
+use alloc::{format, vec, vec::Vec};
+use core::fmt::{self, Write};
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::ast::Node;
+use crate::lexer::{spanned_tokens, Span};
+use crate::parser::{parse, ParseResult};
+
+/// Writes `Node`s as JSON text into any `fmt::Write` sink.
+///
+/// Compact output has no insignificant whitespace. Pretty output puts every
+/// member/element on its own line, indented by `indent` spaces per level.
+struct Serializer<'a, W: Write> {
+    out: &'a mut W,
+    indent: Option<usize>,
+    level: usize,
+    options: SerializeOptions,
+    /// Write object members sorted by key rather than in document order.
+    sort_keys: bool,
+}
+
+/// Extra escaping to apply to strings and keys when serializing.
+///
+/// By default only what the JSON spec requires is escaped, any other char
+/// is written as is.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Escape every non-ASCII char, astral ones as a surrogate pair like
+    /// `\uD83D\uDE00`, so the output is pure ASCII.
+    pub ascii_only: bool,
+    /// Escape `<`, `>`, `&`, U+2028 and U+2029 so the output can't close a
+    /// `<script>` tag or break a JS string.
+    pub html_safe: bool,
+}
+
+impl<'a, W: Write> Serializer<'a, W> {
+    fn compact(out: &'a mut W) -> Self {
+        Self {
+            out,
+            indent: None,
+            level: 0,
+            options: SerializeOptions::default(),
+            sort_keys: false,
+        }
+    }
+
+    fn pretty(out: &'a mut W, indent: usize) -> Self {
+        Self {
+            out,
+            indent: Some(indent),
+            level: 0,
+            options: SerializeOptions::default(),
+            sort_keys: false,
+        }
+    }
+
+    fn node(&mut self, node: &Node) -> fmt::Result {
+        match node {
+            Node::Object(members) => self.object(members),
+            Node::Array(elements) => self.array(elements),
+            Node::Str(s) => escape_string(self.out, s, self.options),
+            Node::Int(i) => write!(self.out, "{}", i),
+            Node::Float(f) => self.float(*f),
+            Node::True => self.out.write_str("true"),
+            Node::False => self.out.write_str("false"),
+            Node::Null => self.out.write_str("null"),
+        }
+    }
+
+    fn object(&mut self, members: &[(String, Node)]) -> fmt::Result {
+        if members.is_empty() {
+            return self.out.write_str("{}");
+        }
+        self.out.write_char('{')?;
+        self.level += 1;
+        if self.sort_keys {
+            let mut sorted: Vec<_> = members.iter().collect();
+            // Stable, so duplicate keys keep their relative order.
+            sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+            self.members(sorted)?;
+        } else {
+            self.members(members)?;
+        }
+        self.level -= 1;
+        self.newline()?;
+        self.out.write_char('}')
+    }
+
+    fn members<'n, I>(&mut self, members: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = &'n (String, Node)>,
+    {
+        for (i, (key, value)) in members.into_iter().enumerate() {
+            if i > 0 {
+                self.out.write_char(',')?;
+            }
+            self.newline()?;
+            escape_string(self.out, key, self.options)?;
+            self.out.write_char(':')?;
+            if self.indent.is_some() {
+                self.out.write_char(' ')?;
+            }
+            self.node(value)?;
+        }
+        Ok(())
+    }
+
+    fn array(&mut self, elements: &[Node]) -> fmt::Result {
+        if elements.is_empty() {
+            return self.out.write_str("[]");
+        }
+        self.out.write_char('[')?;
+        self.level += 1;
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                self.out.write_char(',')?;
+            }
+            self.newline()?;
+            self.node(element)?;
+        }
+        self.level -= 1;
+        self.newline()?;
+        self.out.write_char(']')
+    }
+
+    /// Non-finite floats have no JSON representation so they are written as
+    /// `null`, mirroring `JSON.stringify`.
+    fn float(&mut self, f: f64) -> fmt::Result {
+        if f.is_finite() {
+            write!(self.out, "{:?}", f)
+        } else {
+            self.out.write_str("null")
+        }
+    }
+
+    /// Starts a new line at the current level. No-op in compact mode.
+    fn newline(&mut self) -> fmt::Result {
+        if let Some(indent) = self.indent {
+            self.out.write_char('\n')?;
+            for _ in 0..indent * self.level {
+                self.out.write_char(' ')?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Writes `s` as a double-quoted JSON string, escaping as required by the spec
+/// and `options`.
+pub(crate) fn escape_string<W: Write>(
+    out: &mut W,
+    s: &str,
+    options: SerializeOptions,
+) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if options.html_safe => {
+                write!(out, "\\u{:04X}", c as u32)?
+            }
+            c if !c.is_ascii() && options.ascii_only => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(out, "\\u{:04X}", unit)?;
+                }
+            }
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\u{0008}' => out.write_str("\\b")?,
+            '\u{000C}' => out.write_str("\\f")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04X}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
+
+// correctness: writing to a `String` never fails, so the helpers below
+// unwrap.
+
+/// Appends `s` to `out` as a double-quoted JSON string with the default
+/// escapes.
+#[cfg(any(feature = "yaml", feature = "toml"))]
+pub(crate) fn push_escaped(out: &mut String, s: &str) {
+    escape_string(out, s, SerializeOptions::default()).unwrap();
+}
+
+/// Appends `value` to `out` as formatted by its `Display` impl.
+pub(crate) fn push_display(out: &mut String, value: impl fmt::Display) {
+    write!(out, "{}", value).unwrap();
+}
+
+/// Error returned by `Node::to_csv_rows`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CsvError {
+    /// The node isn't an array.
+    NotAnArray { found: &'static str },
+    /// The element at `index` isn't an object.
+    NotAnObject { index: usize, found: &'static str },
+}
+
+/// Error returned by `Node::to_ndjson`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializeError {
+    /// The node isn't an array.
+    NotAnArray { found: &'static str },
+}
+
+/// Reformats JSON text without any insignificant whitespace.
+pub fn minify(input: &str) -> ParseResult<String> {
+    Ok(parse(input)?.to_string())
+}
+
+/// Returns the byte length of `input` and of its minified form, e.g. to
+/// report how many bytes minifying saves.
+///
+/// The minified text is only measured, never stored.
+pub fn minify_savings(input: &str) -> ParseResult<(usize, usize)> {
+    let mut counter = ByteCounter(0);
+    // correctness: counting bytes never fails.
+    Serializer::compact(&mut counter)
+        .node(&parse(input)?)
+        .unwrap();
+    Ok((input.len(), counter.0))
+}
+
+/// A `fmt::Write` sink that only counts the bytes written to it.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Where a JSON text has insignificant whitespace, see
+/// `analyze_whitespace`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WhitespaceReport {
+    /// Whitespace before the value.
+    pub leading: Option<Span>,
+    /// Whitespace after the value.
+    pub trailing: Option<Span>,
+    /// Whitespace between two tokens, in order.
+    pub between_tokens: Vec<Span>,
+}
+
+impl WhitespaceReport {
+    /// True if the text has no insignificant whitespace, i.e. it is already
+    /// minified.
+    pub fn is_minified(&self) -> bool {
+        self.leading.is_none() && self.trailing.is_none() && self.between_tokens.is_empty()
+    }
+}
+
+/// Finds the insignificant whitespace in `input`, e.g. to check that it is
+/// minified.
+pub fn analyze_whitespace(input: &str) -> ParseResult<WhitespaceReport> {
+    parse(input)?;
+    let mut report = WhitespaceReport {
+        leading: None,
+        trailing: None,
+        between_tokens: vec![],
+    };
+    let mut end = 0;
+    for (i, (_, span)) in spanned_tokens(input).enumerate() {
+        if span.base > end {
+            let gap = Span::new(end, span.base);
+            if i == 0 {
+                report.leading = Some(gap);
+            } else {
+                report.between_tokens.push(gap);
+            }
+        }
+        end = span.base + span.len;
+    }
+    if input.len() > end {
+        report.trailing = Some(Span::new(end, input.len()));
+    }
+    Ok(report)
+}
+
+/// Reformats JSON text with every member/element on its own line, indented
+/// by `indent` spaces per level.
+pub fn prettify(input: &str, indent: usize) -> ParseResult<String> {
+    Ok(parse(input)?.to_string_pretty(indent))
+}
+
+/// Compact serialization, e.g. `node.to_string()`.
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Serializer::compact(f).node(self)
+    }
+}
+
+/// Pretty printing adapter returned by `Node::pretty`.
+pub struct Pretty<'a> {
+    node: &'a Node,
+    indent: usize,
+    sort_keys: bool,
+}
+
+impl fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut serializer = Serializer::pretty(f, self.indent);
+        serializer.sort_keys = self.sort_keys;
+        serializer.node(self.node)
+    }
+}
+
+/// Compact serialization with `SerializeOptions`.
+struct WithOptions<'a> {
+    node: &'a Node,
+    options: SerializeOptions,
+}
+
+impl fmt::Display for WithOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut serializer = Serializer::compact(f);
+        serializer.options = self.options;
+        serializer.node(self.node)
+    }
+}
+
+impl Node {
+    /// Returns a `Display` adapter that pretty prints this node, indenting
+    /// nested members/elements by `indent` spaces per level.
+    pub fn pretty(&self, indent: usize) -> Pretty<'_> {
+        Pretty {
+            node: self,
+            indent,
+            sort_keys: false,
+        }
+    }
+
+    /// Serializes this node as pretty printed JSON text.
+    pub fn to_string_pretty(&self, indent: usize) -> String {
+        self.pretty(indent).to_string()
+    }
+
+    /// Serializes this node as pretty printed JSON text with the members of
+    /// every object sorted by key, for diff-stable output, e.g. of files kept
+    /// under version control. The node itself is left as is.
+    pub fn to_pretty_sorted_string(&self, indent: usize) -> String {
+        let sorted = Pretty {
+            node: self,
+            indent,
+            sort_keys: true,
+        };
+        sorted.to_string()
+    }
+
+    /// Serializes this node as compact JSON text that is safe to embed in
+    /// HTML, e.g. inside a `<script>` tag.
+    ///
+    /// Same as `to_string` except that `<`, `>`, `&`, U+2028 and U+2029 in
+    /// strings and keys are written as `\u` escapes.
+    pub fn to_html_safe_string(&self) -> String {
+        let options = SerializeOptions {
+            html_safe: true,
+            ..Default::default()
+        };
+        self.to_string_with(&options)
+    }
+
+    /// Serializes this node as compact JSON text, escaping strings and keys
+    /// according to `options`.
+    pub fn to_string_with(&self, options: &SerializeOptions) -> String {
+        WithOptions {
+            node: self,
+            options: *options,
+        }
+        .to_string()
+    }
+
+    /// Serializes the elements of this array as newline delimited JSON
+    /// (NDJSON), one compact record per line, the inverse of `parse_lines`.
+    ///
+    /// Records are separated by `\n`, with none after the last one. Compact
+    /// output escapes line feeds in strings, so a record never spans lines.
+    pub fn to_ndjson(&self) -> Result<String, SerializeError> {
+        let elements = match self {
+            Node::Array(elements) => elements,
+            _ => {
+                return Err(SerializeError::NotAnArray {
+                    found: self.type_name(),
+                })
+            }
+        };
+        let mut out = String::new();
+        for (i, element) in elements.iter().enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            push_display(&mut out, element);
+        }
+        Ok(out)
+    }
+
+    /// Converts an array of objects into a CSV header and rows.
+    ///
+    /// The header is the union of the keys of every object, in order of
+    /// first appearance. Each row has a cell per header column: strings as
+    /// is, other values, nested ones included, as their compact JSON text,
+    /// and an empty cell for a missing key. Cells are not quoted or escaped.
+    pub fn to_csv_rows(&self) -> Result<(Vec<String>, Vec<Vec<String>>), CsvError> {
+        let elements = match self {
+            Node::Array(elements) => elements,
+            _ => {
+                return Err(CsvError::NotAnArray {
+                    found: self.type_name(),
+                })
+            }
+        };
+        let mut header: Vec<String> = vec![];
+        for (index, element) in elements.iter().enumerate() {
+            let members = match element {
+                Node::Object(members) => members,
+                _ => {
+                    return Err(CsvError::NotAnObject {
+                        index,
+                        found: element.type_name(),
+                    })
+                }
+            };
+            for (key, _) in members {
+                if !header.contains(key) {
+                    header.push(key.clone());
+                }
+            }
+        }
+        let rows = elements
+            .iter()
+            .map(|element| {
+                header
+                    .iter()
+                    .map(|key| match element.get(key) {
+                        Some(Node::Str(s)) => s.clone(),
+                        Some(value) => value.to_string(),
+                        None => String::new(),
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok((header, rows))
+    }
+
+    /// The text of a scalar, for values that may be written either as a
+    /// string or not, e.g. a port given as `8080` or `"8080"`.
+    ///
+    /// Strings are returned as is, numbers and booleans as their JSON text.
+    /// `None` for `Null`, containers and non-finite floats, which have no
+    /// JSON text.
+    pub fn to_scalar_string(&self) -> Option<String> {
+        match self {
+            Node::Str(s) => Some(s.clone()),
+            Node::Float(f) if !f.is_finite() => None,
+            Node::Int(_) | Node::Float(_) | Node::True | Node::False => Some(self.to_string()),
+            Node::Object(_) | Node::Array(_) | Node::Null => None,
+        }
+    }
+
+    /// Flattens this node into key/value pairs for a form or query string,
+    /// e.g. `{"a": {"b": [1]}}` becomes `[("a[b][0]", "1")]`.
+    ///
+    /// Nested members use `parent[child]` keys and elements use `parent[i]`.
+    /// Strings are written as is, other scalars as their JSON text. Empty
+    /// containers yield no pairs, nor does a scalar root since it has no key.
+    /// Keys and values are not percent-encoded.
+    pub fn to_query_params(&self) -> Vec<(String, String)> {
+        let mut params = vec![];
+        match self {
+            Node::Object(members) => {
+                for (key, value) in members {
+                    value.query_params_into(key.clone(), &mut params);
+                }
+            }
+            Node::Array(elements) => {
+                for (i, element) in elements.iter().enumerate() {
+                    element.query_params_into(i.to_string(), &mut params);
+                }
+            }
+            _ => (),
+        }
+        params
+    }
+
+    fn query_params_into(&self, prefix: String, params: &mut Vec<(String, String)>) {
+        match self {
+            Node::Object(members) => {
+                for (key, value) in members {
+                    value.query_params_into(format!("{}[{}]", prefix, key), params);
+                }
+            }
+            Node::Array(elements) => {
+                for (i, element) in elements.iter().enumerate() {
+                    element.query_params_into(format!("{}[{}]", prefix, i), params);
+                }
+            }
+            Node::Str(s) => params.push((prefix, s.clone())),
+            scalar => params.push((prefix, scalar.to_string())),
+        }
+    }
+
+    /// Writes this node as compact JSON text into `writer`.
+    #[cfg(feature = "std")]
+    pub fn to_writer<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        write!(writer, "{}", self)
+    }
+
+    /// Writes this node as compact JSON text into `writer`, escaping strings
+    /// and keys according to `options`.
+    #[cfg(feature = "std")]
+    pub fn to_writer_with<W: io::Write>(
+        &self,
+        writer: &mut W,
+        options: &SerializeOptions,
+    ) -> io::Result<()> {
+        let node = WithOptions {
+            node: self,
+            options: *options,
+        };
+        write!(writer, "{}", node)
+    }
+
+    /// Writes this node as pretty printed JSON text into `writer`.
+    #[cfg(feature = "std")]
+    pub fn to_writer_pretty<W: io::Write>(&self, writer: &mut W, indent: usize) -> io::Result<()> {
+        write!(writer, "{}", self.pretty(indent))
+    }
+
+    /// Serializes this node as compact JSON text into an owned byte buffer.
+    #[cfg(feature = "std")]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        // correctness: writing into a `Vec<u8>` never fails.
+        self.to_writer(&mut buf).unwrap();
+        buf
+    }
+
+    /// Serializes this node as pretty printed JSON text into an owned byte
+    /// buffer.
+    #[cfg(feature = "std")]
+    pub fn to_vec_pretty(&self, indent: usize) -> Vec<u8> {
+        let mut buf = Vec::new();
+        // correctness: writing into a `Vec<u8>` never fails.
+        self.to_writer_pretty(&mut buf, indent).unwrap();
+        buf
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        analyze_whitespace, minify, minify_savings, prettify, CsvError, SerializeError,
+        SerializeOptions,
+    };
+    use crate::ast::Node;
+    use crate::lexer::Span;
+    use crate::parser::parse;
+    use crate::stream::{parse_lines, StreamErrorPolicy};
+
+    const SAMPLE: &str = "[{ \"name\": \"Adrien\", \"age\": 23, \"hungry\": true, \"health\": 0.9, \"girlfriend\": null, \"tags\": [] }]";
+
+    #[test]
+    fn it_serializes_compact() {
+        let node = parse(SAMPLE).unwrap();
+        let expected = "[{\"name\":\"Adrien\",\"age\":23,\"hungry\":true,\"health\":0.9,\"girlfriend\":null,\"tags\":[]}]";
+        assert_eq!(node.to_string(), expected);
+    }
+
+    #[test]
+    fn it_serializes_pretty() {
+        let node = parse("{\"a\": [1, 2.5], \"b\": {}}").unwrap();
+        let expected = "{\n  \"a\": [\n    1,\n    2.5\n  ],\n  \"b\": {}\n}";
+        assert_eq!(node.to_string_pretty(2), expected);
+    }
+
+    #[test]
+    fn it_escapes_strings() {
+        let node = Node::Str("\"\\\n\u{0001}é".to_owned());
+        assert_eq!(node.to_string(), "\"\\\"\\\\\\n\\u0001é\"");
+        assert_eq!(parse(&node.to_string()), Ok(node));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn it_serializes_to_vec() {
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.to_vec(), node.to_string().into_bytes());
+        assert_eq!(node.to_vec_pretty(4), node.to_string_pretty(4).into_bytes());
+    }
+
+    #[test]
+    fn it_pretty_prints_with_sorted_keys() {
+        let node = parse("{\"b\": [{\"z\": 1, \"y\": 2}], \"a\": {}, \"c\": null}").unwrap();
+        let expected = "{\n  \"a\": {},\n  \"b\": [\n    {\n      \"y\": 2,\n      \"z\": 1\n    }\n  ],\n  \"c\": null\n}";
+        assert_eq!(node.to_pretty_sorted_string(2), expected);
+        let reordered = parse("{\"c\": null, \"a\": {}, \"b\": [{\"y\": 2, \"z\": 1}]}").unwrap();
+        assert_eq!(reordered.to_pretty_sorted_string(2), expected);
+        assert_eq!(node.keys(), Some(vec!["b", "a", "c"]));
+    }
+
+    #[test]
+    fn it_serializes_ndjson() {
+        let node = parse("[{\"id\": 1, \"msg\": \"a\\nb\"}, {\"id\": 2}, [true]]").unwrap();
+        let ndjson = node.to_ndjson().unwrap();
+        assert_eq!(ndjson.lines().count(), 3);
+        assert_eq!(ndjson, "{\"id\":1,\"msg\":\"a\\nb\"}\n{\"id\":2}\n[true]");
+
+        let records: Vec<_> = parse_lines(&ndjson, StreamErrorPolicy::StopOnError)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(Node::Array(records), node);
+
+        assert_eq!(Node::Array(vec![]).to_ndjson(), Ok(String::new()));
+        assert_eq!(
+            Node::Null.to_ndjson(),
+            Err(SerializeError::NotAnArray { found: "null" })
+        );
+    }
+
+    #[test]
+    fn it_stringifies_scalars() {
+        assert_eq!(
+            Node::Str("8080".into()).to_scalar_string(),
+            Some("8080".into())
+        );
+        assert_eq!(Node::Int(8080).to_scalar_string(), Some("8080".into()));
+        assert_eq!(Node::Int(-1).to_scalar_string(), Some("-1".into()));
+        assert_eq!(Node::Float(0.5).to_scalar_string(), Some("0.5".into()));
+        assert_eq!(Node::Float(f64::NAN).to_scalar_string(), None);
+        assert_eq!(Node::True.to_scalar_string(), Some("true".into()));
+        assert_eq!(Node::False.to_scalar_string(), Some("false".into()));
+        assert_eq!(Node::Null.to_scalar_string(), None);
+        assert_eq!(Node::Array(vec![]).to_scalar_string(), None);
+        assert_eq!(Node::Object(vec![]).to_scalar_string(), None);
+    }
+
+    #[test]
+    fn it_flattens_into_query_params() {
+        let node = parse("{\"q\": \"a b\", \"page\": 2, \"filter\": {\"tags\": [\"x\", null], \"min\": 1.5}, \"empty\": []}").unwrap();
+        assert_eq!(
+            node.to_query_params(),
+            [
+                ("q".to_owned(), "a b".to_owned()),
+                ("page".to_owned(), "2".to_owned()),
+                ("filter[tags][0]".to_owned(), "x".to_owned()),
+                ("filter[tags][1]".to_owned(), "null".to_owned()),
+                ("filter[min]".to_owned(), "1.5".to_owned()),
+            ]
+        );
+        assert!(Node::True.to_query_params().is_empty());
+    }
+
+    #[test]
+    fn it_serializes_html_safe_strings() {
+        let node = parse("{\"<b>\": \"</script> & \\u2028\\u2029\"}").unwrap();
+        let expected = "{\"\\u003Cb\\u003E\":\"\\u003C/script\\u003E \\u0026 \\u2028\\u2029\"}";
+        assert_eq!(node.to_html_safe_string(), expected);
+        assert_eq!(parse(expected), Ok(node));
+
+        let node = parse(SAMPLE).unwrap();
+        assert_eq!(node.to_html_safe_string(), node.to_string());
+    }
+
+    #[test]
+    fn it_minifies_and_prettifies() {
+        let input = "\n  { \"a\" :\t[ 1 ,  2 ] ,\r\n \"b\" : { } }  \n";
+        assert_eq!(minify(input).unwrap(), "{\"a\":[1,2],\"b\":{}}");
+        let expected = "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}";
+        assert_eq!(prettify(input, 2).unwrap(), expected);
+        assert!(minify("[1,").is_err());
+    }
+
+    #[test]
+    fn it_converts_to_csv_rows() {
+        let node = parse("[{\"name\": \"Adrien\", \"age\": 23}, {\"name\": \"Bob\", \"tags\": [1, \"a\"]}, {\"age\": null}]").unwrap();
+        let (header, rows) = node.to_csv_rows().unwrap();
+        assert_eq!(header, ["name", "age", "tags"]);
+        assert_eq!(
+            rows,
+            [
+                ["Adrien", "23", ""],
+                ["Bob", "", "[1,\"a\"]"],
+                ["", "null", ""],
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_csv_rows_that_are_not_objects() {
+        assert_eq!(
+            parse("{}").unwrap().to_csv_rows(),
+            Err(CsvError::NotAnArray { found: "object" })
+        );
+        assert_eq!(
+            parse("[{}, 1]").unwrap().to_csv_rows(),
+            Err(CsvError::NotAnObject {
+                index: 1,
+                found: "number"
+            })
+        );
+    }
+
+    #[test]
+    fn it_measures_minify_savings() {
+        let input = "{\n  \"a\" : [ 1 , 2 ] ,\n  \"b\" : \"x y\"\n}\n";
+        let (original, minified) = minify_savings(input).unwrap();
+        assert_eq!(original, input.len());
+        assert_eq!(minified, minify(input).unwrap().len());
+        assert!(minified < original);
+    }
+
+    #[test]
+    fn it_analyzes_whitespace() {
+        let report = analyze_whitespace("{\"a b\":[1,2]}").unwrap();
+        assert!(report.is_minified());
+
+        let report = analyze_whitespace(" {\"a b\": [1,\n 2]}\n").unwrap();
+        assert!(!report.is_minified());
+        assert_eq!(report.leading, Some(Span::new(0, 1)));
+        assert_eq!(report.between_tokens, [Span::new(8, 9), Span::new(12, 14)]);
+        assert_eq!(report.trailing, Some(Span::new(17, 18)));
+
+        assert!(analyze_whitespace("[1,").is_err());
+    }
+
+    #[test]
+    fn it_serializes_ascii_only() {
+        let node = Node::Object(vec![("clé".into(), Node::Str("😀 ü".into()))]);
+        let options = SerializeOptions {
+            ascii_only: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            node.to_string_with(&options),
+            "{\"cl\\u00E9\":\"\\uD83D\\uDE00 \\u00FC\"}"
+        );
+        assert_eq!(
+            node.to_string_with(&SerializeOptions::default()),
+            "{\"clé\":\"😀 ü\"}"
+        );
+        assert_eq!(node.to_string(), "{\"clé\":\"😀 ü\"}");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn it_writes_ascii_only() {
+        let node = Node::Str("😀".into());
+        let options = SerializeOptions {
+            ascii_only: true,
+            ..Default::default()
+        };
+        let mut buf = Vec::new();
+        node.to_writer_with(&mut buf, &options).unwrap();
+        assert_eq!(buf, b"\"\\uD83D\\uDE00\"");
+    }
+}
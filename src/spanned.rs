@@ -0,0 +1,155 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::iter::Peekable;
+
+use crate::ast::Node;
+use crate::lexer::{spanned_tokens, token, Span, TokenKind};
+use crate::parser::{parse, ParseResult};
+
+/// A parsed JSON value along with the span of its text in the input, and of
+/// the text of every value nested in it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedNode {
+    /// From the first to the last byte of the value, delimiters included.
+    pub span: Span,
+    pub value: SpannedValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpannedValue {
+    Object(Vec<(String, SpannedNode)>),
+    Array(Vec<SpannedNode>),
+    /// Any node but an object or an array.
+    Scalar(Node),
+}
+
+/// Parses `input` like `parse`, keeping the span of every value.
+pub fn parse_spanned(input: &str) -> ParseResult<SpannedNode> {
+    // Validate first so that building below can assume well-formed input.
+    parse(input)?;
+    Ok(build(&mut spanned_tokens(input).peekable()))
+}
+
+fn build<I: Iterator<Item = (TokenKind, Span)>>(tokens: &mut Peekable<I>) -> SpannedNode {
+    // correctness: the input was parsed successfully, so tokens can't run
+    // out before the value is complete and are in a valid order.
+    let (kind, span) = tokens.next().unwrap();
+    let value = match kind {
+        token::OpenBracket => {
+            let mut members = vec![];
+            loop {
+                match tokens.next().unwrap() {
+                    (token::CloseBracket, close) => {
+                        return enclose(span, close, SpannedValue::Object(members))
+                    }
+                    (token::Str(key), _) => {
+                        tokens.next();
+                        members.push((key, build(tokens)));
+                    }
+                    _ => (),
+                }
+            }
+        }
+        token::OpenSquare => {
+            let mut elements = vec![];
+            loop {
+                match tokens.peek().unwrap() {
+                    (token::CloseSquare, _) => {
+                        let (_, close) = tokens.next().unwrap();
+                        return enclose(span, close, SpannedValue::Array(elements));
+                    }
+                    (token::Comma, _) => {
+                        tokens.next();
+                    }
+                    _ => elements.push(build(tokens)),
+                }
+            }
+        }
+        token::Str(s) => Node::Str(s),
+        token::Int(i) => Node::Int(i),
+        token::Float(f) => Node::Float(f),
+        token::True => Node::True,
+        token::False => Node::False,
+        token::Null => Node::Null,
+        _ => unreachable!("not the start of a value"),
+    };
+    SpannedNode {
+        span,
+        value: SpannedValue::Scalar(value),
+    }
+}
+
+/// A container spanning from its `open` delimiter to its `close` one.
+fn enclose(open: Span, close: Span, value: SpannedValue) -> SpannedNode {
+    SpannedNode {
+        span: Span::new(open.base, close.base + close.len),
+        value,
+    }
+}
+
+impl SpannedNode {
+    /// Finds the innermost value whose span contains the byte at `offset`,
+    /// e.g. the value under the cursor in an editor.
+    ///
+    /// Object keys aren't values, so an offset within a key finds the object.
+    pub fn node_at_offset(&self, offset: usize) -> Option<&SpannedNode> {
+        if offset < self.span.base || offset >= self.span.base + self.span.len {
+            return None;
+        }
+        let inner = match &self.value {
+            SpannedValue::Object(members) => members
+                .iter()
+                .find_map(|(_, value)| value.node_at_offset(offset)),
+            SpannedValue::Array(elements) => elements
+                .iter()
+                .find_map(|element| element.node_at_offset(offset)),
+            SpannedValue::Scalar(_) => None,
+        };
+        inner.or(Some(self))
+    }
+
+    /// Drops the spans, converting back into a plain `Node`.
+    pub fn to_node(&self) -> Node {
+        match &self.value {
+            SpannedValue::Object(members) => Node::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_node()))
+                    .collect(),
+            ),
+            SpannedValue::Array(elements) => {
+                Node::Array(elements.iter().map(SpannedNode::to_node).collect())
+            }
+            SpannedValue::Scalar(node) => node.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_spanned;
+    use crate::lexer::Span;
+    use crate::parser::parse;
+
+    const SAMPLE: &str = "{\"a\": [1, {\"b\": null}], \"c\": \"x\"}";
+
+    #[test]
+    fn it_keeps_the_parsed_value() {
+        let spanned = parse_spanned(SAMPLE).unwrap();
+        assert_eq!(spanned.span, Span::new(0, SAMPLE.len()));
+        assert_eq!(spanned.to_node(), parse(SAMPLE).unwrap());
+        assert_eq!(parse_spanned("[1,"), Err(parse("[1,").unwrap_err()));
+    }
+
+    #[test]
+    fn it_finds_the_innermost_node_at_an_offset() {
+        let spanned = parse_spanned(SAMPLE).unwrap();
+        let span_at = |offset| spanned.node_at_offset(offset).map(|node| node.span.clone());
+        assert_eq!(span_at(7), Some(Span::new(7, 8)));
+        assert_eq!(span_at(17), Some(Span::new(16, 20)));
+        assert_eq!(span_at(15), Some(Span::new(10, 21)));
+        assert_eq!(span_at(9), Some(Span::new(6, 22)));
+        assert_eq!(span_at(2), Some(Span::new(0, 33)));
+        assert_eq!(span_at(30), Some(Span::new(29, 32)));
+        assert_eq!(span_at(33), None);
+    }
+}
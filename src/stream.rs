@@ -0,0 +1,357 @@
+use alloc::string::String;
+
+use crate::ast::Node;
+use crate::lexer::{Span, StrError, TokenKind};
+use crate::parser::{parse, ParseError, ParseErrorKind, ParseResult};
+
+/// Outcome of feeding a chunk into a `StreamingParser`.
+#[derive(Debug, PartialEq)]
+pub enum Status {
+    /// The buffered input ends mid-value, more input is needed.
+    Pending,
+    /// A complete value was parsed.
+    Ready(Node),
+}
+
+/// Parses a single JSON value that arrives in chunks.
+///
+/// Chunks are buffered and the whole buffer is reparsed on every `feed`, so
+/// feeding a value in `n` chunks costs O(n²) in total. It is best suited to a
+/// handful of chunks rather than a byte at a time.
+/// Once a value is `Ready` the buffer is cleared and the parser can be fed
+/// the next document.
+#[derive(Debug, Default)]
+pub struct StreamingParser {
+    buffer: String,
+}
+
+impl StreamingParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `chunk` to the buffered input and tries to parse it.
+    ///
+    /// Errors caused by the input being cut short yield `Status::Pending`,
+    /// any other error is returned as is.
+    pub fn feed(&mut self, chunk: &str) -> Result<Status, ParseError> {
+        self.buffer.push_str(chunk);
+        match parse(&self.buffer) {
+            Ok(node) if self.may_continue(&node) => Ok(Status::Pending),
+            Ok(node) => {
+                self.buffer.clear();
+                Ok(Status::Ready(node))
+            }
+            Err(err) if self.is_truncation(&err) => Ok(Status::Pending),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Signals the end of the input, parsing whatever is buffered.
+    ///
+    /// Unlike `feed`, truncated input is an error here.
+    pub fn finish(self) -> ParseResult {
+        parse(&self.buffer)
+    }
+
+    /// A number or keyword touching the end of the buffer might be the prefix
+    /// of a longer one, e.g. `12` of `123`.
+    fn may_continue(&self, node: &Node) -> bool {
+        let ends_with_whitespace = self.buffer.ends_with(char::is_whitespace);
+        match node {
+            Node::Object(_) | Node::Array(_) | Node::Str(_) => false,
+            _ => !ends_with_whitespace,
+        }
+    }
+
+    /// Whether `err` may go away with more input: the input ended early, or
+    /// the offending token touches the end of the buffer and may be the
+    /// start of a longer one, e.g. the `1.` of `1.5` or the `e` of `1e5`.
+    fn is_truncation(&self, err: &ParseError) -> bool {
+        let at_end = err.span.base + err.span.len == self.buffer.len()
+            || self.is_truncated_exponent(err.span.base);
+        let token = match &err.kind {
+            ParseErrorKind::UnexpectedEof | ParseErrorKind::EmptyInput => return true,
+            ParseErrorKind::ExpectedValue(TokenKind::Eof) => return true,
+            ParseErrorKind::InvalidStr(StrError::Unterminated) => return true,
+            ParseErrorKind::InvalidIdent(_) | ParseErrorKind::UnknownToken(_) => return at_end,
+            ParseErrorKind::UnexpectedContinuation(token)
+            | ParseErrorKind::UnexpectedToken(token)
+            | ParseErrorKind::ExpectedColon(token)
+            | ParseErrorKind::ExpectedComma(token)
+            | ParseErrorKind::ExpectedValue(token)
+            | ParseErrorKind::NonStringKey(token) => token,
+            _ => return false,
+        };
+        at_end && matches!(token, TokenKind::InvalidIdent(_) | TokenKind::Unknown(_))
+    }
+
+    /// Whether the buffer from `base` on is an exponent cut short after its
+    /// sign, e.g. the `e+` of `1e+5`. The lexer splits it off the number as
+    /// an `e` and a lone sign, so the error only spans the `e`.
+    fn is_truncated_exponent(&self, base: usize) -> bool {
+        let after_digit = self.buffer[..base].ends_with(|c: char| c.is_ascii_digit());
+        after_digit && matches!(&self.buffer.as_bytes()[base..], [b'e' | b'E', b'+' | b'-'])
+    }
+}
+
+/// What the multi-document parsers do with a malformed record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamErrorPolicy {
+    /// Yield the error and end the stream.
+    StopOnError,
+    /// Drop the record and carry on with the next one.
+    SkipToNext,
+}
+
+/// Applies `policy` to a stream of per-record results.
+fn with_policy<I: Iterator<Item = ParseResult>>(
+    mut results: I,
+    policy: StreamErrorPolicy,
+) -> impl Iterator<Item = ParseResult> {
+    let mut stopped = false;
+    core::iter::from_fn(move || loop {
+        if stopped {
+            return None;
+        }
+        match results.next()? {
+            Err(_) if policy == StreamErrorPolicy::SkipToNext => continue,
+            Err(err) => {
+                stopped = true;
+                return Some(Err(err));
+            }
+            ok => return Some(ok),
+        }
+    })
+}
+
+/// Parses newline delimited JSON (NDJSON), one value per line.
+///
+/// Lines are parsed like `parse`, so surrounding whitespace, including a
+/// `\r` before the line feed, is ignored, and blank lines are skipped. Error
+/// spans are relative to the whole input.
+pub fn parse_lines(
+    input: &str,
+    policy: StreamErrorPolicy,
+) -> impl Iterator<Item = ParseResult> + '_ {
+    let mut offset = 0;
+    let results = input.split('\n').filter_map(move |line| {
+        let base = offset;
+        offset += line.len() + 1;
+        if line.trim().is_empty() {
+            return None;
+        }
+        Some(parse(line).map_err(|mut err| {
+            err.span.base += base;
+            err
+        }))
+    });
+    with_policy(results, policy)
+}
+
+/// Record separator starting every record of a JSON text sequence.
+const RS: char = '\u{1E}';
+
+/// Parses a JSON text sequence (RFC 7464), yielding one result per record.
+///
+/// Every record is an RS char followed by a JSON text, usually terminated by
+/// a line feed. Empty records are skipped. A number or keyword that isn't
+/// followed by whitespace may have been cut short, so it is reported as
+/// `UnexpectedEof` unless it is the last record. Error spans are relative to
+/// the whole input.
+pub fn parse_seq(input: &str) -> impl Iterator<Item = ParseResult> + '_ {
+    let mut records = input.split(RS).peekable();
+    let mut offset = 0;
+    core::iter::from_fn(move || loop {
+        let record = records.next()?;
+        let base = offset;
+        offset += record.len() + RS.len_utf8();
+        if record.trim().is_empty() {
+            continue;
+        }
+        let is_last = records.peek().is_none();
+        let result = match parse(record) {
+            Ok(node) => match node {
+                Node::Object(_) | Node::Array(_) | Node::Str(_) => Ok(node),
+                _ if is_last || record.ends_with(char::is_whitespace) => Ok(node),
+                _ => {
+                    let end = record.len();
+                    Err(ParseError::new(
+                        ParseErrorKind::UnexpectedEof,
+                        Span::new(end, end),
+                    ))
+                }
+            },
+            Err(err) => Err(err),
+        };
+        return Some(result.map_err(|mut err| {
+            err.span.base += base;
+            err
+        }));
+    })
+}
+
+/// Like `parse_seq`, with `policy` deciding what happens to the stream
+/// after a malformed record.
+pub fn parse_seq_with(
+    input: &str,
+    policy: StreamErrorPolicy,
+) -> impl Iterator<Item = ParseResult> + '_ {
+    with_policy(parse_seq(input), policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_an_object_split_across_chunks() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.feed("{\"name\": \"Adr"), Ok(Status::Pending));
+        assert_eq!(parser.feed("ien\", \"hungry\": tr"), Ok(Status::Pending));
+        assert_eq!(
+            parser.feed("ue}"),
+            Ok(Status::Ready(Node::Object(vec![
+                ("name".into(), Node::Str("Adrien".into())),
+                ("hungry".into(), Node::True),
+            ])))
+        );
+    }
+
+    #[test]
+    fn it_waits_for_numbers_to_end() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.feed("12"), Ok(Status::Pending));
+        assert_eq!(parser.feed("3"), Ok(Status::Pending));
+        assert_eq!(parser.feed(" "), Ok(Status::Ready(Node::Int(123))));
+    }
+
+    #[test]
+    fn it_finishes_a_pending_scalar() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.feed("nu"), Ok(Status::Pending));
+        assert_eq!(parser.feed("ll"), Ok(Status::Pending));
+        assert_eq!(parser.finish(), Ok(Node::Null));
+    }
+
+    #[test]
+    fn it_waits_for_numbers_split_mid_token() {
+        for (first, second, expected) in [
+            ("1.", "5 ", Node::Float(1.5)),
+            ("1e", "5 ", Node::Float(1e5)),
+            ("1e+", "5 ", Node::Float(1e5)),
+            ("[1e-", "2]", Node::Array(vec![Node::Float(1e-2)])),
+            ("[1.5E-", "1]", Node::Array(vec![Node::Float(0.15)])),
+            ("-", "1 ", Node::Int(-1)),
+        ] {
+            let mut parser = StreamingParser::new();
+            assert_eq!(parser.feed(first), Ok(Status::Pending), "{:?}", first);
+            assert_eq!(parser.feed(second), Ok(Status::Ready(expected)));
+        }
+
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.feed("[1.2e"), Ok(Status::Pending));
+        assert_eq!(parser.feed("+3, 1."), Ok(Status::Pending));
+        assert_eq!(
+            parser.feed("5]"),
+            Ok(Status::Ready(Node::Array(vec![
+                Node::Float(1.2e3),
+                Node::Float(1.5)
+            ])))
+        );
+    }
+
+    #[test]
+    fn it_reports_genuine_errors() {
+        let mut parser = StreamingParser::new();
+        assert!(parser.feed("[e+").is_err());
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.feed("[1, "), Ok(Status::Pending));
+        assert!(parser.feed("]").is_err());
+    }
+
+    #[test]
+    fn it_rejects_truncated_input_on_finish() {
+        let mut parser = StreamingParser::new();
+        assert_eq!(parser.feed("[1"), Ok(Status::Pending));
+        assert!(parser.finish().is_err());
+    }
+
+    #[test]
+    fn it_parses_a_json_text_sequence() {
+        let input = "\u{1E}{\"a\": 1}\n\u{1E}[true, null]\n";
+        let nodes: Vec<_> = parse_seq(input).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                Ok(Node::Object(vec![("a".into(), Node::Int(1))])),
+                Ok(Node::Array(vec![Node::True, Node::Null])),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_tolerates_a_last_record_without_line_feed() {
+        let nodes: Vec<_> = parse_seq("\u{1E}1\n\u{1E}\u{1E}2").collect();
+        assert_eq!(nodes, vec![Ok(Node::Int(1)), Ok(Node::Int(2))]);
+    }
+
+    #[test]
+    fn it_reports_truncated_records() {
+        let nodes: Vec<_> = parse_seq("\u{1E}12\u{1E}{\"a\":\n\u{1E}3\n").collect();
+        assert_eq!(
+            nodes,
+            vec![
+                Err(ParseError::new(
+                    ParseErrorKind::UnexpectedEof,
+                    Span::new(3, 3)
+                )),
+                Err(ParseError::new(
+                    ParseErrorKind::ExpectedValue(TokenKind::Eof),
+                    Span::new(10, 10)
+                )),
+                Ok(Node::Int(3)),
+            ]
+        );
+    }
+
+    const BAD_LINES: &str = "  {\"a\": 1}\r\n\n[1,\n  true  \n";
+
+    #[test]
+    fn it_stops_parsing_lines_on_error() {
+        let nodes: Vec<_> = parse_lines(BAD_LINES, StreamErrorPolicy::StopOnError).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                Ok(Node::Object(vec![("a".into(), Node::Int(1))])),
+                Err(ParseError::new(
+                    ParseErrorKind::ExpectedValue(TokenKind::Eof),
+                    Span::new(16, 16)
+                )),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_skips_bad_lines() {
+        let nodes: Vec<_> = parse_lines(BAD_LINES, StreamErrorPolicy::SkipToNext).collect();
+        assert_eq!(
+            nodes,
+            vec![
+                Ok(Node::Object(vec![("a".into(), Node::Int(1))])),
+                Ok(Node::True),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_applies_the_error_policy_to_sequences() {
+        let input = "\u{1E} 1\n\u{1E}[1,\n\u{1E}2\n";
+        let nodes: Vec<_> = parse_seq_with(input, StreamErrorPolicy::SkipToNext).collect();
+        assert_eq!(nodes, vec![Ok(Node::Int(1)), Ok(Node::Int(2))]);
+
+        let nodes: Vec<_> = parse_seq_with(input, StreamErrorPolicy::StopOnError).collect();
+        assert_eq!(nodes.len(), 2);
+        assert!(nodes[1].is_err());
+    }
+}
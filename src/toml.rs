@@ -0,0 +1,172 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::ast::Node;
+use crate::ser::{push_display, push_escaped};
+
+/// Error returned by `Node::to_toml_string` for nodes TOML can't represent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TomlError {
+    /// A TOML document is a table, so the root must be an object.
+    RootNotAnObject { found: &'static str },
+    /// TOML has no null value.
+    Null,
+    /// A table has two members with this key.
+    DuplicateKey(String),
+}
+
+impl Node {
+    /// Converts this object into a TOML document.
+    ///
+    /// Nested objects become `[parent.child]` tables, written after the other
+    /// members of their parent. Objects within arrays become inline tables.
+    /// Non-finite floats become `nan`, `inf` or `-inf`.
+    pub fn to_toml_string(&self) -> Result<String, TomlError> {
+        let members = match self {
+            Node::Object(members) => members,
+            _ => {
+                return Err(TomlError::RootNotAnObject {
+                    found: self.type_name(),
+                })
+            }
+        };
+        let mut out = String::new();
+        write_table(&mut out, members, &mut Vec::new())?;
+        Ok(out)
+    }
+}
+
+/// Writes the members of the table at `path`, then its sub-tables.
+fn write_table<'a>(
+    out: &mut String,
+    members: &'a [(String, Node)],
+    path: &mut Vec<&'a str>,
+) -> Result<(), TomlError> {
+    check_duplicates(members)?;
+    for (key, value) in members {
+        if !matches!(value, Node::Object(_)) {
+            write_key(out, key);
+            out.push_str(" = ");
+            write_inline(out, value)?;
+            out.push('\n');
+        }
+    }
+    for (key, value) in members {
+        if let Node::Object(children) = value {
+            path.push(key);
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            out.push('[');
+            for (i, key) in path.iter().enumerate() {
+                if i > 0 {
+                    out.push('.');
+                }
+                write_key(out, key);
+            }
+            out.push_str("]\n");
+            write_table(out, children, path)?;
+            path.pop();
+        }
+    }
+    Ok(())
+}
+
+fn write_inline(out: &mut String, node: &Node) -> Result<(), TomlError> {
+    match node {
+        Node::Object(members) if members.is_empty() => out.push_str("{}"),
+        Node::Object(members) => {
+            check_duplicates(members)?;
+            out.push_str("{ ");
+            for (i, (key, value)) in members.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_key(out, key);
+                out.push_str(" = ");
+                write_inline(out, value)?;
+            }
+            out.push_str(" }");
+        }
+        Node::Array(elements) => {
+            out.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_inline(out, element)?;
+            }
+            out.push(']');
+        }
+        Node::Null => return Err(TomlError::Null),
+        Node::Str(s) => push_escaped(out, s),
+        Node::Float(f) if f.is_nan() => out.push_str("nan"),
+        Node::Float(f) if f.is_infinite() && *f > 0.0 => out.push_str("inf"),
+        Node::Float(f) if f.is_infinite() => out.push_str("-inf"),
+        other => push_display(out, other),
+    }
+    Ok(())
+}
+
+/// Writes `key` bare if TOML allows it, quoted otherwise.
+fn write_key(out: &mut String, key: &str) {
+    let is_bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if is_bare {
+        out.push_str(key);
+    } else {
+        push_escaped(out, key);
+    }
+}
+
+fn check_duplicates(members: &[(String, Node)]) -> Result<(), TomlError> {
+    for (i, (key, _)) in members.iter().enumerate() {
+        if members[..i].iter().any(|(k, _)| k == key) {
+            return Err(TomlError::DuplicateKey(key.clone()));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TomlError;
+    use crate::ast::Node;
+    use crate::parser::parse;
+
+    #[test]
+    fn it_converts_to_toml() {
+        let node = parse("{\"name\": \"app\", \"server\": {\"port\": 80, \"tls\": {\"on\": true}}, \"hosts\": [\"a\", {\"b c\": 1.5}], \"meta\": {}}").unwrap();
+        let expected = "\
+name = \"app\"
+hosts = [\"a\", { \"b c\" = 1.5 }]
+
+[server]
+port = 80
+
+[server.tls]
+on = true
+
+[meta]
+";
+        assert_eq!(node.to_toml_string().unwrap(), expected);
+    }
+
+    #[test]
+    fn it_rejects_what_toml_cannot_represent() {
+        assert_eq!(
+            parse("[1]").unwrap().to_toml_string(),
+            Err(TomlError::RootNotAnObject { found: "array" })
+        );
+        assert_eq!(
+            parse("{\"a\": [null]}").unwrap().to_toml_string(),
+            Err(TomlError::Null)
+        );
+        assert_eq!(
+            parse("{\"a\": 1, \"a\": 2}").unwrap().to_toml_string(),
+            Err(TomlError::DuplicateKey("a".into()))
+        );
+        assert_eq!(Node::Object(vec![]).to_toml_string(), Ok("".into()));
+    }
+}
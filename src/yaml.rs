@@ -0,0 +1,124 @@
+use alloc::string::String;
+
+use crate::ast::Node;
+use crate::ser::{push_display, push_escaped};
+
+impl Node {
+    /// Converts this node into a block style YAML document.
+    ///
+    /// String values are always double quoted, and keys only when they could
+    /// be mistaken for another type, with the same escapes as JSON. Every
+    /// node can be represented, non-finite floats becoming `.nan`, `.inf` or `-.inf`.
+    pub fn to_yaml_string(&self) -> String {
+        let mut out = String::new();
+        match self {
+            Node::Object(members) if !members.is_empty() => write_members(&mut out, members, 0),
+            Node::Array(elements) if !elements.is_empty() => write_elements(&mut out, elements, 0),
+            scalar => {
+                write_scalar(&mut out, scalar);
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+/// Writes `node` after a `key:` or `-`, its children indented by `indent`.
+fn write_value(out: &mut String, node: &Node, indent: usize) {
+    match node {
+        Node::Object(members) if !members.is_empty() => {
+            out.push('\n');
+            write_members(out, members, indent);
+        }
+        Node::Array(elements) if !elements.is_empty() => {
+            out.push('\n');
+            write_elements(out, elements, indent);
+        }
+        scalar => {
+            out.push(' ');
+            write_scalar(out, scalar);
+            out.push('\n');
+        }
+    }
+}
+
+fn write_members(out: &mut String, members: &[(String, Node)], indent: usize) {
+    for (key, value) in members {
+        pad(out, indent);
+        if is_plain(key) {
+            out.push_str(key);
+        } else {
+            push_escaped(out, key);
+        }
+        out.push(':');
+        write_value(out, value, indent + 2);
+    }
+}
+
+fn write_elements(out: &mut String, elements: &[Node], indent: usize) {
+    for element in elements {
+        pad(out, indent);
+        out.push('-');
+        write_value(out, element, indent + 2);
+    }
+}
+
+/// Writes a scalar or an empty container in flow style.
+fn write_scalar(out: &mut String, node: &Node) {
+    match node {
+        Node::Object(_) => out.push_str("{}"),
+        Node::Array(_) => out.push_str("[]"),
+        Node::Str(s) => push_escaped(out, s),
+        Node::Float(f) if f.is_nan() => out.push_str(".nan"),
+        Node::Float(f) if f.is_infinite() && *f > 0.0 => out.push_str(".inf"),
+        Node::Float(f) if f.is_infinite() => out.push_str("-.inf"),
+        other => push_display(out, other),
+    }
+}
+
+/// Whether `key` can be written without quotes and still be read back as
+/// the same string.
+fn is_plain(key: &str) -> bool {
+    const RESERVED: [&str; 9] = ["y", "n", "yes", "no", "on", "off", "true", "false", "null"];
+    let mut chars = key.chars();
+    let starts_well = matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_');
+    starts_well
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !RESERVED.iter().any(|word| key.eq_ignore_ascii_case(word))
+}
+
+fn pad(out: &mut String, indent: usize) {
+    for _ in 0..indent {
+        out.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::Node;
+    use crate::parser::parse;
+
+    #[test]
+    fn it_converts_to_yaml() {
+        let node = parse("{\"name\": \"Adrien\", \"tags\": [1, {\"a b\": null, \"on\": 2.5}, []], \"meta\": {}, \"ok\": true}").unwrap();
+        let expected = "\
+name: \"Adrien\"
+tags:
+  - 1
+  -
+    \"a b\": null
+    \"on\": 2.5
+  - []
+meta: {}
+ok: true
+";
+        assert_eq!(node.to_yaml_string(), expected);
+    }
+
+    #[test]
+    fn it_converts_scalars_to_yaml() {
+        assert_eq!(Node::Str("a\nb".into()).to_yaml_string(), "\"a\\nb\"\n");
+        assert_eq!(Node::Float(f64::NEG_INFINITY).to_yaml_string(), "-.inf\n");
+        assert_eq!(Node::Array(vec![]).to_yaml_string(), "[]\n");
+    }
+}